@@ -2,21 +2,31 @@
  * Licensed GPL version 3 (details at the end of the file) */
 
 use postgres::{Client, NoTls};
-use std::collections::{BTreeMap, BTreeSet};
-use time::{OffsetDateTime};
-use rand::Rng;
+use std::collections::{BTreeMap, BTreeSet, VecDeque};
+use time::{Date, Month, OffsetDateTime, PrimitiveDateTime, Time, UtcOffset};
+use rand::{Rng, SeedableRng};
+use rand::rngs::StdRng;
 use rand::prelude::SliceRandom;
 use postgres::types::{ToSql, to_sql_checked, FromSql};
+use postgres::binary_copy::BinaryCopyInWriter;
+use postgres::error::SqlState;
+use std::fmt;
+use std::sync::{Arc, Mutex, RwLock};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::time::{Duration, Instant};
 
 #[derive(Debug)]
 pub struct TypedString {
     pub value: String,
 }
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord)]
 enum Value {
+    Int2(i16),
+    Int4(i32),
     Int8(i64),
     Text(String),
+    Uuid(uuid::Uuid),
 }
 
 impl ToSql for TypedString {
@@ -24,56 +34,452 @@ impl ToSql for TypedString {
         self.value.to_sql(ty, out)
     }
 
-    fn accepts(ty: &postgres::types::Type) -> bool {
+    fn accepts(_ty: &postgres::types::Type) -> bool {
         true
     }
 
     to_sql_checked!();
 }
 
+/// A `numeric` value, sent in the binary format of Postgres. A finite value consists of its decimal `digits`, of
+/// which the last `scale` ones are behind the decimal point.
+#[derive(Debug)]
+pub enum Numeric {
+    Finite {negative: bool, digits: String, scale: i32},
+    NaN,
+    Infinity,
+    NegativeInfinity,
+}
+
+impl ToSql for Numeric {
+    fn to_sql(&self, _ty: &postgres::types::Type, out: &mut postgres::types::private::BytesMut) -> Result<postgres::types::IsNull, Box<dyn std::error::Error + Send + Sync>> {
+        let (sign, groups, weight, dscale) = match self {
+            Numeric::NaN => (0xC000u16, Vec::new(), 0, 0),
+            Numeric::Infinity => (0xD000, Vec::new(), 0, 0),
+            Numeric::NegativeInfinity => (0xF000, Vec::new(), 0, 0),
+            Numeric::Finite {negative, digits, scale} => {
+                let (integer, fraction) = if *scale <= 0 {
+                    (format!("{}{}", digits, "0".repeat(-scale as usize)), String::new())
+                } else {
+                    let digits = format!("{:0>width$}", digits, width = *scale as usize);
+                    let (integer, fraction) = digits.split_at(digits.len() - *scale as usize);
+                    (integer.to_string(), fraction.to_string())
+                };
+
+                // Postgres stores groups of four decimal digits, aligned at the decimal point
+                let integer = format!("{}{}", "0".repeat((4 - integer.len() % 4) % 4), integer);
+                let fraction = format!("{}{}", fraction, "0".repeat((4 - fraction.len() % 4) % 4));
+                let mut groups = integer.as_bytes().chunks(4)
+                    .chain(fraction.as_bytes().chunks(4))
+                    .map(|group| std::str::from_utf8(group).unwrap().parse::<i16>().unwrap())
+                    .collect::<Vec<i16>>();
+
+                let mut weight = (integer.len() / 4) as i16 - 1;
+                while groups.first() == Some(&0) {
+                    groups.remove(0);
+                    weight -= 1;
+                }
+                while groups.last() == Some(&0) {
+                    groups.pop();
+                }
+                if groups.is_empty() {
+                    weight = 0;
+                }
+
+                (if *negative && !groups.is_empty() {0x4000} else {0}, groups, weight, (*scale).max(0) as u16)
+            },
+        };
+
+        out.extend_from_slice(&(groups.len() as i16).to_be_bytes());
+        out.extend_from_slice(&weight.to_be_bytes());
+        out.extend_from_slice(&sign.to_be_bytes());
+        out.extend_from_slice(&dscale.to_be_bytes());
+        for group in groups {
+            out.extend_from_slice(&group.to_be_bytes());
+        }
+
+        Ok(postgres::types::IsNull::No)
+    }
+
+    fn accepts(ty: &postgres::types::Type) -> bool {
+        *ty == postgres::types::Type::NUMERIC
+    }
+
+    to_sql_checked!();
+}
+
+/// A `timetz` value, for which the `time` crate has no type
+#[derive(Debug)]
+pub struct TimeTz {
+    pub time: Time,
+    pub offset: UtcOffset,
+}
+
+impl ToSql for TimeTz {
+    fn to_sql(&self, _ty: &postgres::types::Type, out: &mut postgres::types::private::BytesMut) -> Result<postgres::types::IsNull, Box<dyn std::error::Error + Send + Sync>> {
+        let (hour, minute, second, microsecond) = self.time.as_hms_micro();
+        let microseconds = ((hour as i64 * 60 + minute as i64) * 60 + second as i64) * 1_000_000 + microsecond as i64;
+        out.extend_from_slice(&microseconds.to_be_bytes());
+        // Postgres counts the offset in seconds west of UTC
+        out.extend_from_slice(&(-self.offset.whole_seconds()).to_be_bytes());
+
+        Ok(postgres::types::IsNull::No)
+    }
+
+    fn accepts(ty: &postgres::types::Type) -> bool {
+        *ty == postgres::types::Type::TIMETZ
+    }
+
+    to_sql_checked!();
+}
+
+/// An `interval` value, for which the `time` crate has no type
+#[derive(Debug)]
+pub struct Interval {
+    pub months: i32,
+    pub days: i32,
+    pub microseconds: i64,
+}
+
+impl ToSql for Interval {
+    fn to_sql(&self, _ty: &postgres::types::Type, out: &mut postgres::types::private::BytesMut) -> Result<postgres::types::IsNull, Box<dyn std::error::Error + Send + Sync>> {
+        out.extend_from_slice(&self.microseconds.to_be_bytes());
+        out.extend_from_slice(&self.days.to_be_bytes());
+        out.extend_from_slice(&self.months.to_be_bytes());
+
+        Ok(postgres::types::IsNull::No)
+    }
+
+    fn accepts(ty: &postgres::types::Type) -> bool {
+        *ty == postgres::types::Type::INTERVAL
+    }
+
+    to_sql_checked!();
+}
+
+/// An `inet` or `cidr` value. For `cidr`, the bits of `address` after `netmask` have to be zero.
+#[derive(Debug)]
+pub struct Inet {
+    pub address: std::net::IpAddr,
+    pub netmask: u8,
+}
+
+impl ToSql for Inet {
+    fn to_sql(&self, ty: &postgres::types::Type, out: &mut postgres::types::private::BytesMut) -> Result<postgres::types::IsNull, Box<dyn std::error::Error + Send + Sync>> {
+        // Postgres' own address family numbers, not the ones of the operating system
+        let (family, octets) = match self.address {
+            std::net::IpAddr::V4(address) => (2u8, address.octets().to_vec()),
+            std::net::IpAddr::V6(address) => (3u8, address.octets().to_vec()),
+        };
+        out.extend_from_slice(&[family, self.netmask, (*ty == postgres::types::Type::CIDR) as u8, octets.len() as u8]);
+        out.extend_from_slice(&octets);
+
+        Ok(postgres::types::IsNull::No)
+    }
+
+    fn accepts(ty: &postgres::types::Type) -> bool {
+        *ty == postgres::types::Type::INET || *ty == postgres::types::Type::CIDR
+    }
+
+    to_sql_checked!();
+}
+
+/// A `macaddr` or `macaddr8` value of 6 or 8 bytes
+#[derive(Debug)]
+pub struct MacAddr {
+    pub bytes: Vec<u8>,
+}
+
+impl ToSql for MacAddr {
+    fn to_sql(&self, _ty: &postgres::types::Type, out: &mut postgres::types::private::BytesMut) -> Result<postgres::types::IsNull, Box<dyn std::error::Error + Send + Sync>> {
+        out.extend_from_slice(&self.bytes);
+
+        Ok(postgres::types::IsNull::No)
+    }
+
+    fn accepts(ty: &postgres::types::Type) -> bool {
+        ty.name() == "macaddr" || ty.name() == "macaddr8"
+    }
+
+    to_sql_checked!();
+}
+
+/// A `bit` or `varbit` value of `length` bits, the last byte padded with zeros
+#[derive(Debug)]
+pub struct BitString {
+    pub length: i32,
+    pub bytes: Vec<u8>,
+}
+
+impl ToSql for BitString {
+    fn to_sql(&self, _ty: &postgres::types::Type, out: &mut postgres::types::private::BytesMut) -> Result<postgres::types::IsNull, Box<dyn std::error::Error + Send + Sync>> {
+        out.extend_from_slice(&self.length.to_be_bytes());
+        out.extend_from_slice(&self.bytes);
+
+        Ok(postgres::types::IsNull::No)
+    }
+
+    fn accepts(ty: &postgres::types::Type) -> bool {
+        *ty == postgres::types::Type::BIT || *ty == postgres::types::Type::VARBIT
+    }
+
+    to_sql_checked!();
+}
+
+/// Writes `value` prefixed with its length, as the elements of arrays, ranges and composite values are. Elements of a domain type are
+/// written as their base type.
+fn write_element(value: Option<&(dyn ToSql + Sync)>, mut ty: &postgres::types::Type, out: &mut postgres::types::private::BytesMut) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+    while let postgres::types::Kind::Domain(base_type) = ty.kind() {
+        ty = base_type;
+    }
+
+    let start = out.len();
+    out.extend_from_slice(&[0; 4]);
+    let length = match value.map(|value| value.to_sql_checked(ty, out)).transpose()? {
+        Some(postgres::types::IsNull::No) => (out.len() - start - 4) as i32,
+        _ => -1,
+    };
+    out[start..start + 4].copy_from_slice(&length.to_be_bytes());
+    Ok(())
+}
+
+/// A range value. A missing bound is unbounded, the flag tells whether the bound is inclusive.
+#[derive(Debug)]
+pub enum RangeValue {
+    Empty,
+    Bounds {
+        lower: Option<(Box<dyn ToSql + Sync>, bool)>,
+        upper: Option<(Box<dyn ToSql + Sync>, bool)>,
+    },
+}
+
+impl RangeValue {
+    fn write(&self, subtype: &postgres::types::Type, out: &mut postgres::types::private::BytesMut) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+        match self {
+            RangeValue::Empty => out.extend_from_slice(&[0x01]),
+            RangeValue::Bounds {lower, upper} => {
+                let lower_flags = match lower {None => 0x08, Some((_, true)) => 0x02, Some((_, false)) => 0};
+                let upper_flags = match upper {None => 0x10, Some((_, true)) => 0x04, Some((_, false)) => 0};
+                out.extend_from_slice(&[lower_flags | upper_flags]);
+                for (bound, _) in lower.iter().chain(upper) {
+                    write_element(Some(bound.as_ref()), subtype, out)?;
+                }
+            },
+        }
+        Ok(())
+    }
+}
+
+impl ToSql for RangeValue {
+    fn to_sql(&self, ty: &postgres::types::Type, out: &mut postgres::types::private::BytesMut) -> Result<postgres::types::IsNull, Box<dyn std::error::Error + Send + Sync>> {
+        let postgres::types::Kind::Range(subtype) = ty.kind() else {
+            return Err(format!("{} is not a range type", ty).into());
+        };
+        self.write(subtype, out)?;
+
+        Ok(postgres::types::IsNull::No)
+    }
+
+    fn accepts(ty: &postgres::types::Type) -> bool {
+        matches!(ty.kind(), postgres::types::Kind::Range(_))
+    }
+
+    to_sql_checked!();
+}
+
+#[derive(Debug)]
+pub struct MultiRange {
+    pub ranges: Vec<RangeValue>,
+}
+
+impl ToSql for MultiRange {
+    fn to_sql(&self, ty: &postgres::types::Type, out: &mut postgres::types::private::BytesMut) -> Result<postgres::types::IsNull, Box<dyn std::error::Error + Send + Sync>> {
+        let postgres::types::Kind::Multirange(subtype) = ty.kind() else {
+            return Err(format!("{} is not a multirange type", ty).into());
+        };
+
+        out.extend_from_slice(&(self.ranges.len() as i32).to_be_bytes());
+        for range in &self.ranges {
+            let start = out.len();
+            out.extend_from_slice(&[0; 4]);
+            range.write(subtype, out)?;
+            let length = (out.len() - start - 4) as i32;
+            out[start..start + 4].copy_from_slice(&length.to_be_bytes());
+        }
+
+        Ok(postgres::types::IsNull::No)
+    }
+
+    fn accepts(ty: &postgres::types::Type) -> bool {
+        matches!(ty.kind(), postgres::types::Kind::Multirange(_))
+    }
+
+    to_sql_checked!();
+}
+
+/// An array with the lengths of its `dimensions` and its elements in row-major order, `None` being `NULL`
+#[derive(Debug)]
+pub struct ArrayValue {
+    pub dimensions: Vec<i32>,
+    pub elements: Vec<Option<Box<dyn ToSql + Sync>>>,
+}
+
+impl ToSql for ArrayValue {
+    fn to_sql(&self, ty: &postgres::types::Type, out: &mut postgres::types::private::BytesMut) -> Result<postgres::types::IsNull, Box<dyn std::error::Error + Send + Sync>> {
+        let postgres::types::Kind::Array(member) = ty.kind() else {
+            return Err(format!("{} is not an array type", ty).into());
+        };
+
+        out.extend_from_slice(&(self.dimensions.len() as i32).to_be_bytes());
+        out.extend_from_slice(&(self.elements.iter().any(Option::is_none) as i32).to_be_bytes());
+        out.extend_from_slice(&member.oid().to_be_bytes());
+        for dimension in &self.dimensions {
+            out.extend_from_slice(&dimension.to_be_bytes());
+            out.extend_from_slice(&1i32.to_be_bytes());
+        }
+
+        for element in &self.elements {
+            write_element(element.as_deref(), member, out)?;
+        }
+
+        Ok(postgres::types::IsNull::No)
+    }
+
+    fn accepts(ty: &postgres::types::Type) -> bool {
+        matches!(ty.kind(), postgres::types::Kind::Array(_))
+    }
+
+    to_sql_checked!();
+}
+
+/// A composite value with the values of its fields in order, `None` being `NULL`
+#[derive(Debug)]
+pub struct CompositeValue {
+    pub fields: Vec<Option<Box<dyn ToSql + Sync>>>,
+}
+
+impl ToSql for CompositeValue {
+    fn to_sql(&self, ty: &postgres::types::Type, out: &mut postgres::types::private::BytesMut) -> Result<postgres::types::IsNull, Box<dyn std::error::Error + Send + Sync>> {
+        let postgres::types::Kind::Composite(fields) = ty.kind() else {
+            return Err(format!("{} is not a composite type", ty).into());
+        };
+        if fields.len() != self.fields.len() {
+            return Err(format!("{} has {} fields instead of {}", ty, fields.len(), self.fields.len()).into());
+        }
+
+        out.extend_from_slice(&(fields.len() as i32).to_be_bytes());
+        for (field, value) in fields.iter().zip(&self.fields) {
+            out.extend_from_slice(&field.type_().oid().to_be_bytes());
+            write_element(value.as_deref(), field.type_(), out)?;
+        }
+
+        Ok(postgres::types::IsNull::No)
+    }
+
+    fn accepts(ty: &postgres::types::Type) -> bool {
+        matches!(ty.kind(), postgres::types::Kind::Composite(_))
+    }
+
+    to_sql_checked!();
+}
+
 impl ToSql for Value {
     fn to_sql(&self, ty: &postgres::types::Type, out: &mut postgres::types::private::BytesMut) -> Result<postgres::types::IsNull, Box<dyn std::error::Error + Send + Sync>> {
         match self {
+            Value::Int2(v) => v.to_sql(ty, out),
+            Value::Int4(v) => v.to_sql(ty, out),
             Value::Int8(v) => v.to_sql(ty, out),
             Value::Text(v) => v.to_sql(ty, out),
+            Value::Uuid(v) => v.to_sql(ty, out),
         }
     }
 
     fn accepts(ty: &postgres::types::Type) -> bool {
-        ty.name() == "text" || ty.name() == "int8"
+        <i16 as ToSql>::accepts(ty) || <i32 as ToSql>::accepts(ty) || <i64 as ToSql>::accepts(ty) || <String as ToSql>::accepts(ty) || <uuid::Uuid as ToSql>::accepts(ty)
     }
 
     to_sql_checked!();
 }
 
 impl FromSql<'_> for Value {
-    fn from_sql<'a>(ty: &postgres::types::Type, raw: &'a [u8]) -> Result<Self, Box<dyn std::error::Error + Sync + Send>> {
-        if ty.name() == "text" {
+    fn from_sql(ty: &postgres::types::Type, raw: &[u8]) -> Result<Self, Box<dyn std::error::Error + Sync + Send>> {
+        if <String as FromSql>::accepts(ty) {
             Ok(Value::Text(String::from_sql(ty, raw)?))
         }
+        else if ty.name() == "int2" {
+            Ok(Value::Int2(i16::from_sql(ty, raw)?))
+        }
+        else if ty.name() == "int4" {
+            Ok(Value::Int4(i32::from_sql(ty, raw)?))
+        }
         else if ty.name() == "int8" {
             Ok(Value::Int8(i64::from_sql(ty, raw)?))
         }
+        else if ty.name() == "uuid" {
+            Ok(Value::Uuid(uuid::Uuid::from_sql(ty, raw)?))
+        }
         else {
             panic!("unknown type: {}", ty.name());
         }
     }
     fn accepts(ty: &postgres::types::Type) -> bool {
-        ty.name() == "text" || ty.name() == "int8"
+        <i16 as FromSql>::accepts(ty) || <i32 as FromSql>::accepts(ty) || <i64 as FromSql>::accepts(ty) || <String as FromSql>::accepts(ty) || <uuid::Uuid as FromSql>::accepts(ty)
     }
 }
 
 #[derive(Debug)]
 enum Type {
     Bool,
+    Int2,
     Int4,
     Int8,
-    Text,
+    Text(Option<i32>),
+    Uuid,
     ByteArray,
-    JSON,
+    Json,
+    Jsonb,
+    Date,
+    Time,
+    TimeTz,
+    Interval,
     Timestamp,
+    TimestampTz,
+    Numeric {precision: Option<i32>, scale: Option<i32>},
+    Float4,
+    Float8,
+    Inet,
+    Cidr,
+    MacAddr,
+    MacAddr8,
+    Bit(i32),
+    VarBit(Option<i32>),
     Enum(Vec<String>),
-    Array(Box<Type>),
+    Array(Box<Type>, i32),
+    Range(Box<Type>),
+    MultiRange(Box<Type>),
+    Composite(Vec<(String, Type, Vec<Check>)>),
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord)]
+struct TableName {
+    pub schema: String,
+    pub name: String,
+}
+
+impl TableName {
+    fn quoted(&self) -> String {
+        format!("{}.{}", quote_identifier(&self.schema), quote_identifier(&self.name))
+    }
+}
+
+impl fmt::Display for TableName {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}.{}", self.schema, self.name)
+    }
+}
+
+fn quote_identifier(identifier: &str) -> String {
+    format!("\"{}\"", identifier.replace('"', "\"\""))
 }
 
 #[derive(Debug)]
@@ -81,439 +487,3558 @@ struct Column {
     pub name: String,
     pub value_type: Type,
     pub value_nullable: bool,
-    pub value_default: bool,
+    pub value_default: Option<String>,
     pub primary_key: bool,
-    pub foreign_key: Option<(String, String)>,
+    pub unique: bool,
+    pub generated: bool,
+    pub identity: bool,
+    pub identity_always: bool,
+    pub type_name: String,
+    pub json_schema: Option<serde_json::Value>,
+    pub string_length: Option<(usize, usize)>,
+    pub checks: Vec<Check>,
+    pub type_specifier: Option<String>,
 }
 
-#[derive(Debug, Default)]
-struct Table {
-    pub name: String,
-    pub columns: BTreeMap<String, Column>,
-    pub column_names: Vec<String>,
+/// A condition on the values of a column that can be satisfied when generating them. The flags of `Minimum` and
+/// `Maximum` tell whether the bound is inclusive, `Before` names a column with larger values. `Unparsed` stands for a
+/// condition that is not understood, which generated values can violate.
+#[derive(Debug, Clone, PartialEq)]
+enum Check {
+    Minimum(f64, bool),
+    Maximum(f64, bool),
+    NotEqual(String),
+    OneOf(Vec<String>),
+    Matches(String),
+    MinLength(usize),
+    MaxLength(usize),
+    NotNull,
+    Before(String),
+    Unparsed,
 }
 
-#[derive(Debug, Default)]
-struct Database {
-    pub tables: BTreeMap<String, Table>,
-    pub table_names: Vec<String>,
+#[derive(Debug)]
+struct ForeignKey {
+    pub name: String,
+    pub table: TableName,
+    pub columns: Vec<(String, String)>,
+    pub nullable: bool,
+    pub deferrable: bool,
+    pub cyclic: bool,
 }
 
-struct InsertInformation {
-    table: String,
-    data: Vec<(String, Option<(i32, Box<(dyn postgres::types::ToSql + Sync)>, Option<String>)>)>,
+impl ForeignKey {
+    fn referenced_columns(&self) -> Vec<String> {
+        self.columns.iter()
+            .map(|(_, fcolumn)| fcolumn.clone())
+            .collect()
+    }
+
+    /// Whether the values of the foreign key of `table` can be kept as keys
+    fn has_key_type(&self, table: &Table) -> bool {
+        self.columns.iter().all(|(column, _)| is_key_type(&table.columns[column].value_type))
+    }
 }
 
-fn rand_int() -> i32 {
-    let mut rng = rand::thread_rng();
+fn is_key_type(value_type: &Type) -> bool {
+    matches!(value_type, Type::Int2 | Type::Int4 | Type::Int8 | Type::Text(_) | Type::Uuid)
+}
 
-    let v = match rng.gen_range(0..10) {
-        0 => 0,
-        1 => rng.gen_range(0..3),
-        2 => rng.gen_range(0..10),
-        3 => rng.gen_range(0..30),
-        4 => rng.gen_range(0..100),
-        5 => rng.gen_range(0..300),
-        6 => rng.gen_range(0..1000),
-        7 => rng.gen_range(0..10000),
-        8 => rng.gen_range(0..100000),
-        _ => rng.gen_range(0..1000000),
-    };
-    if 0 == rng.gen_range(0..10) {-v} else {v}
+#[derive(Debug)]
+struct Table {
+    pub name: TableName,
+    pub columns: BTreeMap<String, Column>,
+    pub column_names: Vec<String>,
+    pub foreign_keys: Vec<ForeignKey>,
+    pub referenced_keys: Vec<Vec<String>>,
+    pub unique_keys: Vec<Vec<String>>,
 }
 
-fn rand_str() -> String {
-    use rand::distributions::DistString;
-    let mut rng = rand::thread_rng();
+impl Table {
+    /// Whether some check of the table was not understood, so that generated rows can violate it
+    fn unparsed_checks(&self) -> bool {
+        self.columns.values().any(|column| column.checks.contains(&Check::Unparsed) || unparsed_attribute_checks(&column.value_type))
+    }
 
-    let len = rng.gen_range(0..50);
+    /// Turns the checks that generated values are not made to satisfy into `Unparsed`, so that rows violating them
+    /// are generated again. Values of foreign keys and generated columns are not checked, and ordered pairs are only
+    /// generated for two columns of the same type that have no other checks and are in no other pair.
+    fn mark_unsatisfiable_checks(&mut self) {
+        let foreign_key_columns = self.foreign_keys.iter()
+            .flat_map(|foreign_key| foreign_key.columns.iter().map(|(column, _)| column.clone()))
+            .collect::<BTreeSet<String>>();
+        let pairs = self.columns.values()
+            .flat_map(|column| column.checks.iter().filter_map(move |check| match check {
+                Check::Before(other) => Some((column.name.clone(), other.clone())),
+                _ => None,
+            }))
+            .collect::<Vec<(String, String)>>();
 
-    match rng.gen_range(0..10) {
-        0 => rng.sample_iter::<char, _>(rand::distributions::Standard)
-            .take(len)
-            .collect(),
-        _ => rand::distributions::Alphanumeric.sample_string(&mut rand::thread_rng(), len),
+        let free = |column: &Column| !column.generated && !column.identity
+            && !foreign_key_columns.contains(&column.name)
+            && column.checks.iter().all(|check| matches!(check, Check::Before(_) | Check::NotNull))
+            && pairs.iter().filter(|(lower, upper)| *lower == column.name || *upper == column.name).count() == 1;
+        let ordered = pairs.iter()
+            .filter(|(lower, upper)| {
+                let (lower, upper) = (&self.columns[lower], &self.columns[upper]);
+                orderable(&lower.value_type, &upper.value_type) && free(lower) && free(upper)
+            })
+            .cloned()
+            .collect::<Vec<(String, String)>>();
+
+        for column in self.columns.values_mut() {
+            let fixed = column.generated || foreign_key_columns.contains(&column.name);
+            for check in column.checks.iter_mut() {
+                let satisfiable = match check {
+                    Check::NotNull | Check::Unparsed => true,
+                    Check::Before(other) => ordered.iter().any(|(lower, upper)| *lower == column.name && upper == other),
+                    Check::OneOf(_) if !matches!(column.value_type, Type::Array(..)) => !fixed,
+                    _ => !fixed && satisfiable(&column.value_type, check),
+                };
+                if !satisfiable {
+                    println!("Can not generate values of {} for {:?}", column.name, check);
+                    *check = Check::Unparsed;
+                }
+            }
+            mark_unsatisfiable_attribute_checks(&mut column.value_type);
+        }
     }
 }
 
-impl Database {
-    fn insert_in_table(&self, client: &mut postgres::Transaction, table: &str, set_column: Option<(&str, Value)>, return_column: Option<&str>) -> Result<Option<Value>, postgres::Error> {
-        let mut rng = rand::thread_rng();
-        let mut data: Vec<(String, Option<(i32, Box<(dyn postgres::types::ToSql + Sync)>, Option<String>)>)> = Vec::new();
+/// Turns the checks of the attributes of composite types that generated values are not made to satisfy into
+/// `Unparsed`
+fn mark_unsatisfiable_attribute_checks(value_type: &mut Type) {
+    match value_type {
+        Type::Array(element_type, _) => mark_unsatisfiable_attribute_checks(element_type),
+        Type::Composite(attributes) => {
+            for (_, attribute_type, checks) in attributes {
+                for check in checks.iter_mut().filter(|check| !satisfiable(attribute_type, check)) {
+                    *check = Check::Unparsed;
+                }
+                mark_unsatisfiable_attribute_checks(attribute_type);
+            }
+        },
+        _ => (),
+    }
+}
+
+fn unparsed_attribute_checks(value_type: &Type) -> bool {
+    match value_type {
+        Type::Array(element_type, _) => unparsed_attribute_checks(element_type),
+        Type::Composite(attributes) => attributes.iter()
+            .any(|(_, attribute_type, checks)| checks.contains(&Check::Unparsed) || unparsed_attribute_checks(attribute_type)),
+        _ => false,
+    }
+}
+
+/// Whether `rand_bounds` generates ordered pairs of values for columns of `lower` and `upper`
+fn orderable(lower: &Type, upper: &Type) -> bool {
+    match (lower, upper) {
+        (Type::Numeric {precision, scale}, Type::Numeric {precision: upper_precision, scale: upper_scale}) => precision == upper_precision && scale == upper_scale,
+        (Type::Int2 | Type::Int4 | Type::Int8 | Type::Float4 | Type::Float8 | Type::Date | Type::Timestamp | Type::TimestampTz, _) => std::mem::discriminant(lower) == std::mem::discriminant(upper),
+        _ => false,
+    }
+}
 
-        let mut counter = 0;
-        for column in &self.tables[table].column_names {
-            println!("  {}", column);
+/// Whether `rand_checked` generates values of `value_type` that satisfy `check`, which is not `Before`. Other than for
+/// the elements of arrays and composite types, the constants of `OneOf` can also be cast from text by the database.
+fn satisfiable(value_type: &Type, check: &Check) -> bool {
+    match (value_type, check) {
+        (Type::Array(element_type, _), _) => satisfiable(element_type, check),
+        (_, Check::NotNull | Check::Unparsed) => true,
+        (_, Check::OneOf(_)) => converts_text(value_type),
+        (Type::Int2 | Type::Int4 | Type::Int8 | Type::Numeric {..} | Type::Float4 | Type::Float8, Check::Minimum(..) | Check::Maximum(..) | Check::NotEqual(_)) => true,
+        (Type::Text(_), Check::NotEqual(_) | Check::Matches(_) | Check::MinLength(_) | Check::MaxLength(_)) => true,
+        (Type::Enum(_), Check::NotEqual(_)) => true,
+        _ => false,
+    }
+}
 
-            let column_info = &self.tables[table].columns[column];
+#[derive(Debug, Default)]
+struct Database {
+    pub tables: BTreeMap<TableName, Table>,
+    pub table_names: Vec<TableName>,
+    pub copy: bool,
+    pub non_finite: bool,
+    pub time_range: (i64, i64),
+    pub uuid_v7: bool,
+    pub array_length: i32,
+    pub array_nulls: bool,
+    pub json_depth: u32,
+    pub json_keys: usize,
+    pub overriding_system_value: bool,
+}
 
-            if set_column.is_some() && &set_column.as_ref().unwrap().0 == column {
-                counter += 1;
-                data.push((column.clone(), Some((counter, Box::new(set_column.as_ref().unwrap().1.clone()), None))));
-            } else if column_info.value_nullable && 0 == rng.gen_range(0..3) {
-                data.push((column.clone(), None));
-            }
-            else if column_info.value_default && 0 != rng.gen_range(0..3) {
-                data.push((column.clone(), None));
-            } else {
-                if let Some((ftable, fcolumn)) = &column_info.foreign_key {
-                    let count: i64 = client.query(&format!("select count(*) from {};", ftable), &[])
-                        .unwrap()
-                        .into_iter()
-                        .map(|row| row.get::<_, i64>(0))
-                        .next()
-                        .unwrap();
-
-                    let value: Box<(dyn postgres::types::ToSql + Sync)> =
-                        match &column_info.value_type {
-                            Type::Int8 => {
-                                let id: i64 = client.query(&format!("select {} from {} limit 1 offset {};", fcolumn, ftable, rng.gen_range(0..count)), &[])
-                                    .unwrap()
-                                    .into_iter()
-                                    .map(|row| row.get::<_, i64>(0))
-                                    .next()
-                                    .unwrap();
-                                Box::new(id)
-                            },
-                            Type::Text => {
-                                let id: String = client.query(&format!("select {} from {} limit 1 offset {};", fcolumn, ftable, rng.gen_range(0..count)), &[])
-                                    .unwrap()
-                                    .into_iter()
-                                    .map(|row| row.get::<_, String>(0))
-                                    .next()
-                                    .unwrap();
-                                Box::new(id)
+/// Keys to pick foreign keys from. The keys are shared between all clones of a pool, while `pending` holds the rows
+/// inserted in the current transaction of one worker. `unique` holds the latest values of the unique keys that can
+/// collide, loaded from the tables and generated since.
+#[derive(Debug, Clone)]
+struct KeyPool {
+    pub keys: Arc<RwLock<Keys>>,
+    pub trees: Arc<RwLock<Trees>>,
+    pub unique: Arc<RwLock<Unique>>,
+    pub pending: Vec<(TableName, Row)>,
+    pub capacity: usize,
+    pub max_depth: Option<u32>,
+}
 
-                            },
-                            _ => panic!("Foreign keys only supported of type int8 and text!"),
-                        };
+fn keep<T>(rng: &mut impl Rng, keys: &mut Vec<T>, key: T, capacity: usize) {
+    if keys.len() < capacity {
+        keys.push(key);
+    } else {
+        let replace = rng.gen_range(0..keys.len());
+        keys[replace] = key;
+    }
+}
 
-                    counter += 1;
-                    data.push((column.clone(), Some((counter, value, None))));
-                }
-                else {
-                    let value: Box<(dyn postgres::types::ToSql + Sync)> =
-                    match &column_info.value_type {
-                        Type::Bool => Box::new(0 == rng.gen_range(0..2)),
-                        Type::Int4 => Box::new(rand_int() as i32),
-                        Type::Int8 => Box::new(rand_int() as i64),
-                        Type::Text => Box::new(rand_str()),
-                        Type::ByteArray => Box::new(Vec::<u8>::new()),
-                        Type::JSON => Box::new("{}"),
-                        Type::Timestamp => Box::new(OffsetDateTime::now_utc()),
-                        Type::Enum(values) => Box::new(TypedString {value: values.choose(&mut rng).unwrap().clone()}),
-                        Type::Array(_) => Box::new(Vec::<String>::new()),
-                    };
+/// Values of a unique key. Beyond the capacity, the oldest values are forgotten.
+#[derive(Debug, Default)]
+struct UniqueValues {
+    pub values: BTreeSet<Vec<Value>>,
+    pub order: VecDeque<Vec<Value>>,
+}
 
-                    counter += 1;
-                    data.push((column.clone(), Some((counter, value, if let Type::JSON = column_info.value_type {Some("JSON".to_string())} else {None}))));
-                }
+impl UniqueValues {
+    fn insert(&mut self, key: Vec<Value>, capacity: usize) {
+        if self.values.insert(key.clone()) {
+            self.order.push_back(key);
+            if self.order.len() > capacity {
+                let oldest = self.order.pop_front().unwrap();
+                self.values.remove(&oldest);
             }
         }
+    }
+}
 
-        let infos = InsertInformation {
-            table: table.to_string(),
-            data,
-        };
+impl KeyPool {
+    fn new(db: &Database, capacity: usize, max_depth: Option<u32>) -> KeyPool {
+        // Foreign keys of other types can't be loaded, their tables are left out
+        let keys = db.tables.values()
+            .flat_map(|table| table.foreign_keys.iter().filter(|foreign_key| foreign_key.has_key_type(table)))
+            .map(|foreign_key| ((foreign_key.table.clone(), foreign_key.referenced_columns()), Vec::new()))
+            .collect();
+
+        let trees = db.tables.values()
+            .filter(|_| max_depth.is_some())
+            .flat_map(|table| table.foreign_keys.iter()
+                      .filter(|foreign_key| foreign_key.table == table.name && foreign_key.has_key_type(table))
+                      .map(|foreign_key| ((table.name.clone(), foreign_key.columns.clone()), Vec::new())))
+            .collect();
+
+        // Unique keys can only be tracked if all their columns are key types. Those with a column that is practically
+        // never generated twice, like a uuid or a wide int8, need no tracking.
+        let unique = db.tables.values()
+            .flat_map(|table| table.unique_keys.iter()
+                      .filter(|columns| columns.iter().all(|column| is_key_type(&table.columns[column].value_type)))
+                      .filter(|columns| !columns.iter().any(|column| {
+                          let column = &table.columns[column];
+                          matches!(column.value_type, Type::Int8 | Type::Uuid)
+                              && column.checks.iter().all(|check| matches!(check, Check::NotNull))
+                              && !table.foreign_keys.iter().any(|foreign_key| foreign_key.columns.iter().any(|(name, _)| *name == column.name))
+                      }))
+                      .map(|columns| ((table.name.clone(), columns.clone()), UniqueValues::default())))
+            .collect();
+
+        KeyPool {
+            keys: Arc::new(RwLock::new(keys)),
+            trees: Arc::new(RwLock::new(trees)),
+            unique: Arc::new(RwLock::new(unique)),
+            pending: Vec::new(),
+            capacity,
+            max_depth,
+        }
+    }
 
-        let column_names = infos.data.iter()
-            .map(|(name, _)| format!("\"{}\"", name))
+    /// Up to `capacity` keys of `columns` without `NULL` values, sampled from larger tables, and the number of such
+    /// rows
+    fn load_keys(&self, rng: &mut impl Rng, client: &mut impl Connection, table: &TableName, columns: &[String]) -> Result<(i64, Vec<Vec<Value>>), postgres::Error> {
+        let columns = columns.iter()
+            .map(|column| quote_identifier(column))
             .collect::<Vec<String>>();
+        let not_null = columns.iter()
+            .map(|column| format!("{} is not null", column))
+            .collect::<Vec<String>>()
+            .join(" and ");
+
+        let count: i64 = client.query(&format!("select count(*) from {} where {};", table.quoted(), not_null), &[])?
+            .into_iter()
+            .map(|row| row.get::<_, i64>(0))
+            .next()
+            .unwrap();
+
+        let sample = if count as usize > self.capacity {
+            format!(" tablesample bernoulli ({}) repeatable ({})", (200.0 * self.capacity as f64 / count as f64).min(100.0), rng.gen::<u32>())
+        } else {
+            String::new()
+        };
+
+        // The sample is sorted to be repeatable and then thinned out evenly, as cutting it off would only keep the
+        // lowest keys
+        let keys = client.query(&format!("select {} from {}{} where {} order by {};", columns.join(", "), table.quoted(), sample, not_null, columns.join(", ")), &[])?
+            .into_iter()
+            .map(|row| (0..columns.len()).map(|i| row.get::<_, Value>(i)).collect())
+            .collect::<Vec<Vec<Value>>>();
+        let keys = if keys.len() > self.capacity {
+            keys.choose_multiple(rng, self.capacity).cloned().collect()
+        } else {
+            keys
+        };
+
+        Ok((count, keys))
+    }
+
+    fn refresh(&self, rng: &mut impl Rng, client: &mut impl Connection) -> Result<(), postgres::Error> {
+        let key_columns = self.keys.read().unwrap().keys().cloned().collect::<Vec<(TableName, Vec<String>)>>();
+        for (table, columns) in key_columns {
+            let (count, keys) = self.load_keys(rng, client, &table, &columns)?;
+            println!("Loaded {} keys of {} for table {}", keys.len(), count, table);
+            self.keys.write().unwrap().insert((table, columns), keys);
+        }
+
+        // Existing values of unique keys are added to those generated since the last refresh
+        let unique_keys = self.unique.read().unwrap().keys().cloned().collect::<Vec<(TableName, Vec<String>)>>();
+        for (table, columns) in unique_keys {
+            let (count, keys) = self.load_keys(rng, client, &table, &columns)?;
+            println!("Loaded {} unique keys of {} for table {}", keys.len(), count, table);
+            let mut unique = self.unique.write().unwrap();
+            let values = unique.get_mut(&(table, columns)).unwrap();
+            for key in keys {
+                values.insert(key, self.capacity);
+            }
+        }
+
+        // Rows of self-referencing tables can only be parents while they are above the maximum depth, so the depth
+        // of the existing rows is calculated by walking down the hierarchies from their roots.
+        let max_depth = self.max_depth.unwrap_or(u32::MAX);
+        let hierarchies = self.trees.read().unwrap().keys().cloned().collect::<Vec<Hierarchy>>();
+        for (table, columns) in hierarchies {
+            let hierarchy = (table.clone(), columns.clone());
+            let fcolumns = columns.iter()
+                .map(|(_, fcolumn)| quote_identifier(fcolumn))
+                .collect::<Vec<String>>();
+            let columns = columns.iter()
+                .map(|(column, _)| quote_identifier(column))
+                .collect::<Vec<String>>();
+            let qualified = |alias: &str, columns: &[String]| columns.iter()
+                .map(|column| format!("{}.{}", alias, column))
+                .collect::<Vec<String>>()
+                .join(", ");
+            let is_null = columns.iter()
+                .map(|column| format!("{} is null", column))
+                .collect::<Vec<String>>()
+                .join(" or ");
+
+            let keys = client.query(&format!("with recursive tree ({fcolumns}, pggenerate_depth) as (select {fcolumns}, 1 from {table} where {is_null} or ({columns}) = ({fcolumns}) union all select {child_fcolumns}, tree.pggenerate_depth + 1 from {table} child join tree on ({child_columns}) = ({tree_fcolumns}) where ({child_columns}) <> ({child_fcolumns}) and tree.pggenerate_depth + 1 < {max_depth}) select {fcolumns}, pggenerate_depth from tree where pggenerate_depth < {max_depth} order by pggenerate_depth, {fcolumns} limit {capacity};",
+                                              fcolumns = fcolumns.join(", "),
+                                              columns = columns.join(", "),
+                                              child_fcolumns = qualified("child", &fcolumns),
+                                              child_columns = qualified("child", &columns),
+                                              tree_fcolumns = qualified("tree", &fcolumns),
+                                              table = table.quoted(),
+                                              is_null = is_null,
+                                              max_depth = max_depth,
+                                              capacity = self.capacity), &[])?
+                .into_iter()
+                .map(|row| ((0..fcolumns.len()).map(|i| row.get::<_, Value>(i)).collect(), row.get::<_, i32>(fcolumns.len()) as u32))
+                .collect::<Vec<DepthKey>>();
+
+            println!("Loaded {} keys below depth {} for table {}", keys.len(), max_depth, table);
+            self.trees.write().unwrap().insert(hierarchy, keys);
+        }
+
+        Ok(())
+    }
+
+    /// Remembers the values of the tracked unique keys of a new row, unless the values of one of them were already
+    /// used. Keys with `NULL` values can be used several times.
+    fn claim_unique(&self, table: &TableName, unique_keys: &[&Vec<String>], row: &Row) -> bool {
+        let mut unique = self.unique.write().unwrap();
+        let keys = unique_keys.iter()
+            .filter(|columns| unique.contains_key(&(table.clone(), columns.to_vec())))
+            .filter_map(|columns| columns.iter()
+                        .map(|column| row.get(column).cloned().flatten())
+                        .collect::<Option<Vec<Value>>>()
+                        .map(|key| ((table.clone(), columns.to_vec()), key)))
+            .collect::<Vec<((TableName, Vec<String>), Vec<Value>)>>();
+
+        if keys.iter().any(|(unique_key, key)| unique[unique_key].values.contains(key)) {
+            return false;
+        }
+        for (unique_key, key) in keys {
+            unique.get_mut(&unique_key).unwrap().insert(key, self.capacity);
+        }
+        true
+    }
+
+    fn has_keys(&self, table: &TableName, columns: &[String]) -> bool {
+        self.keys.read().unwrap().get(&(table.clone(), columns.to_vec()))
+            .is_some_and(|keys| !keys.is_empty())
+            || self.pending_keys(table, columns).next().is_some()
+    }
+
+    fn pending_keys<'a>(&'a self, table: &'a TableName, columns: &'a [String]) -> impl Iterator<Item = Vec<Value>> + 'a {
+        self.pending.iter()
+            .filter(move |(ptable, _)| ptable == table)
+            .filter_map(move |(_, row)| columns.iter()
+                        .map(|column| row.get(column).cloned().flatten())
+                        .collect::<Option<Vec<Value>>>())
+    }
+
+    fn choose(&self, rng: &mut impl Rng, table: &TableName, columns: &[String]) -> Option<Vec<Value>> {
+        self.keys.read().unwrap().get(&(table.clone(), columns.to_vec()))
+            .and_then(|keys| keys.choose(rng))
+            .cloned()
+            .or_else(|| self.pending_keys(table, columns).collect::<Vec<Vec<Value>>>().choose(rng).cloned())
+    }
+
+    fn choose_parent(&self, rng: &mut impl Rng, table: &TableName, foreign_key: &ForeignKey) -> Option<Vec<Value>> {
+        if self.max_depth.is_some() {
+            self.trees.read().unwrap().get(&(table.clone(), foreign_key.columns.clone()))
+                .and_then(|keys| keys.choose(rng))
+                .map(|(key, _)| key.clone())
+        } else {
+            self.choose(rng, table, &foreign_key.referenced_columns())
+        }
+    }
+
+    fn add(&self, rng: &mut impl Rng, table: &TableName, row: &Row) {
+        for ((_, columns), keys) in self.keys.write().unwrap().iter_mut().filter(|((ktable, _), _)| ktable == table) {
+            let key = columns.iter()
+                .map(|column| row.get(column).cloned().flatten())
+                .collect::<Option<Vec<Value>>>();
+
+            if let Some(key) = key {
+                keep(rng, keys, key, self.capacity);
+            }
+        }
+
+        let max_depth = self.max_depth.unwrap_or(u32::MAX);
+        for ((_, columns), keys) in self.trees.write().unwrap().iter_mut().filter(|((ktable, _), _)| ktable == table) {
+            let key = columns.iter()
+                .map(|(_, fcolumn)| row.get(fcolumn).cloned().flatten())
+                .collect::<Option<Vec<Value>>>();
+            let parent = columns.iter()
+                .map(|(column, _)| row.get(column).cloned().flatten())
+                .collect::<Option<Vec<Value>>>();
+
+            let depth = match (&key, &parent) {
+                (Some(key), Some(parent)) if key != parent => keys.iter()
+                    .find(|(pkey, _)| pkey == parent)
+                    .map(|(_, depth)| depth + 1),
+                _ => Some(1),
+            };
+
+            if let (Some(key), Some(depth)) = (key, depth) {
+                if depth < max_depth {
+                    keep(rng, keys, (key, depth), self.capacity);
+                }
+            }
+        }
+    }
+
+    fn add_pending(&mut self, table: &TableName, row: &Row) {
+        self.pending.push((table.clone(), row.clone()));
+    }
+
+    fn clear_pending(&mut self) {
+        self.pending.clear();
+    }
+}
+
+type Row = BTreeMap<String, Option<Value>>;
+
+/// A self-referencing table together with the columns of its self-referencing foreign key
+type Hierarchy = (TableName, Vec<(String, String)>);
+
+type DepthKey = (Vec<Value>, u32);
+
+type Keys = BTreeMap<(TableName, Vec<String>), Vec<Vec<Value>>>;
+
+type Trees = BTreeMap<Hierarchy, Vec<DepthKey>>;
+
+type Unique = BTreeMap<(TableName, Vec<String>), UniqueValues>;
+
+type InsertData = Vec<(String, Option<(i32, Box<dyn postgres::types::ToSql + Sync>, Option<String>)>)>;
+
+type Statement = (String, Vec<Box<dyn postgres::types::ToSql + Sync>>);
+
+/// Database access of the generator. `pipeline` sends several independent statements, which the asynchronous client
+/// does at once instead of waiting for each result in turn.
+trait Connection {
+    fn query(&mut self, query: &str, params: &[&(dyn ToSql + Sync)]) -> Result<Vec<postgres::Row>, postgres::Error>;
+    fn execute(&mut self, query: &str, params: &[&(dyn ToSql + Sync)]) -> Result<u64, postgres::Error>;
+    fn batch_execute(&mut self, query: &str) -> Result<(), postgres::Error>;
+    fn column_types(&mut self, query: &str) -> Result<Vec<postgres::types::Type>, postgres::Error>;
+    fn copy_in_binary(&mut self, query: &str, types: &[postgres::types::Type], rows: &[Vec<Box<dyn ToSql + Sync>>]) -> Result<(), postgres::Error>;
+    fn pipeline(&mut self, statements: &[Statement]) -> Result<Vec<Vec<postgres::Row>>, postgres::Error>;
+}
+
+fn statement_params(statements: &[Statement]) -> Vec<Vec<&(dyn ToSql + Sync)>> {
+    statements.iter()
+        .map(|(_, params)| params.iter().map(Box::as_ref).collect())
+        .collect()
+}
+
+impl Connection for Client {
+    fn query(&mut self, query: &str, params: &[&(dyn ToSql + Sync)]) -> Result<Vec<postgres::Row>, postgres::Error> {
+        Client::query(self, query, params)
+    }
+
+    fn execute(&mut self, query: &str, params: &[&(dyn ToSql + Sync)]) -> Result<u64, postgres::Error> {
+        Client::execute(self, query, params)
+    }
+
+    fn batch_execute(&mut self, query: &str) -> Result<(), postgres::Error> {
+        Client::batch_execute(self, query)
+    }
+
+    fn column_types(&mut self, query: &str) -> Result<Vec<postgres::types::Type>, postgres::Error> {
+        Ok(self.prepare(query)?
+           .columns()
+           .iter()
+           .map(|column| column.type_().clone())
+           .collect())
+    }
+
+    fn copy_in_binary(&mut self, query: &str, types: &[postgres::types::Type], rows: &[Vec<Box<dyn ToSql + Sync>>]) -> Result<(), postgres::Error> {
+        let mut writer = BinaryCopyInWriter::new(self.copy_in(query)?, types);
+        for row in rows {
+            let row_refs = row.iter()
+                .map(Box::as_ref).collect::<Vec<&(dyn postgres::types::ToSql + Sync)>>();
+            writer.write(&row_refs)?;
+        }
+        writer.finish()?;
+        Ok(())
+    }
+
+    fn pipeline(&mut self, statements: &[Statement]) -> Result<Vec<Vec<postgres::Row>>, postgres::Error> {
+        statements.iter()
+            .zip(statement_params(statements))
+            .map(|((query, _), params)| Client::query(self, query, &params))
+            .collect()
+    }
+}
+
+/// A `tokio-postgres` connection driven by its own single-threaded runtime
+struct AsyncClient {
+    runtime: tokio::runtime::Runtime,
+    client: tokio_postgres::Client,
+}
+
+impl AsyncClient {
+    fn connect(params: &str) -> Result<AsyncClient, postgres::Error> {
+        let runtime = tokio::runtime::Builder::new_current_thread()
+            .enable_all()
+            .build()
+            .expect("Could not start async runtime");
+
+        let (client, connection) = runtime.block_on(tokio_postgres::connect(params, NoTls))?;
+        runtime.spawn(async move {
+            if let Err(e) = connection.await {
+                println!("{}", e);
+            }
+        });
+
+        Ok(AsyncClient {
+            runtime,
+            client,
+        })
+    }
+}
+
+impl Connection for AsyncClient {
+    fn query(&mut self, query: &str, params: &[&(dyn ToSql + Sync)]) -> Result<Vec<postgres::Row>, postgres::Error> {
+        self.runtime.block_on(self.client.query(query, params))
+    }
+
+    fn execute(&mut self, query: &str, params: &[&(dyn ToSql + Sync)]) -> Result<u64, postgres::Error> {
+        self.runtime.block_on(self.client.execute(query, params))
+    }
+
+    fn batch_execute(&mut self, query: &str) -> Result<(), postgres::Error> {
+        self.runtime.block_on(self.client.batch_execute(query))
+    }
+
+    fn column_types(&mut self, query: &str) -> Result<Vec<postgres::types::Type>, postgres::Error> {
+        Ok(self.runtime.block_on(self.client.prepare(query))?
+           .columns()
+           .iter()
+           .map(|column| column.type_().clone())
+           .collect())
+    }
+
+    fn copy_in_binary(&mut self, query: &str, types: &[postgres::types::Type], rows: &[Vec<Box<dyn ToSql + Sync>>]) -> Result<(), postgres::Error> {
+        self.runtime.block_on(async {
+            let writer = tokio_postgres::binary_copy::BinaryCopyInWriter::new(self.client.copy_in(query).await?, types);
+            futures_util::pin_mut!(writer);
+            for row in rows {
+                let row_refs = row.iter()
+                    .map(Box::as_ref).collect::<Vec<&(dyn postgres::types::ToSql + Sync)>>();
+                writer.as_mut().write(&row_refs).await?;
+            }
+            writer.finish().await?;
+            Ok(())
+        })
+    }
+
+    fn pipeline(&mut self, statements: &[Statement]) -> Result<Vec<Vec<postgres::Row>>, postgres::Error> {
+        let params = statement_params(statements);
+        let queries = statements.iter()
+            .zip(&params)
+            .map(|((query, _), params)| self.client.query(query.as_str(), params));
+        self.runtime.block_on(futures_util::future::try_join_all(queries))
+    }
+}
+
+/// A batch of rows as `INSERT` statements, ready to be sent. `lengths` holds the number of rows of each statement.
+struct Insertion {
+    pub table: TableName,
+    pub statements: Vec<Statement>,
+    pub lengths: Vec<usize>,
+    pub return_columns: Vec<String>,
+    pub ctid: bool,
+}
+
+impl Insertion {
+    fn len(&self) -> usize {
+        self.lengths.iter().sum()
+    }
+
+    fn rows(&self, results: Vec<Vec<postgres::Row>>) -> Vec<Row> {
+        if self.return_columns.is_empty() && !self.ctid {
+            return vec![Row::new(); self.len()];
+        }
+
+        results.into_iter()
+            .flatten()
+            .map(|row| {
+                let mut new_row = self.return_columns.iter()
+                    .enumerate()
+                    .map(|(i, column)| (column.clone(), row.get::<_, Option<Value>>(i)))
+                    .collect::<Row>();
+                if self.ctid {
+                    new_row.insert("ctid".to_string(), Some(Value::Text(row.get::<_, String>(self.return_columns.len()))));
+                }
+                new_row
+            })
+            .collect()
+    }
+}
+
+fn rand_int(rng: &mut impl Rng) -> i32 {
+    let v = match rng.gen_range(0..10) {
+        0 => 0,
+        1 => rng.gen_range(0..3),
+        2 => rng.gen_range(0..10),
+        3 => rng.gen_range(0..30),
+        4 => rng.gen_range(0..100),
+        5 => rng.gen_range(0..300),
+        6 => rng.gen_range(0..1000),
+        7 => rng.gen_range(0..10000),
+        8 => rng.gen_range(0..100000),
+        _ => rng.gen_range(0..1000000),
+    };
+    if 0 == rng.gen_range(0..10) {-v} else {v}
+}
+
+/// A key from a wide range of values, for columns that have to be unique
+fn rand_unique_key(rng: &mut impl Rng, value_type: &Type, string_length: Option<(usize, usize)>) -> Value {
+    match value_type {
+        Type::Int2 => Value::Int2(rng.gen_range(0..=i16::MAX)),
+        Type::Int4 => Value::Int4(rng.gen_range(0..=i32::MAX)),
+        Type::Int8 => Value::Int8(rng.gen_range(0..=i64::MAX)),
+        Type::Text(max_length) => {
+            let (min, max) = string_length.unwrap_or((0, max_length.map_or(49, |max_length| (max_length as usize).min(49))));
+            Value::Text(rand_str(rng, min.max(max.min(16)), max))
+        },
+        _ => rand_key(rng, value_type),
+    }
+}
+
+fn rand_key(rng: &mut impl Rng, value_type: &Type) -> Value {
+    match value_type {
+        Type::Int2 => Value::Int2((rand_int(rng) % 32768) as i16),
+        Type::Int4 => Value::Int4(rand_int(rng)),
+        Type::Int8 => Value::Int8(rand_int(rng) as i64),
+        Type::Text(max_length) => Value::Text(rand_str(rng, 0, max_length.map_or(49, |max_length| (max_length as usize).min(49)))),
+        Type::Uuid => Value::Uuid(rand_uuid(rng, None)),
+        _ => panic!("Foreign keys only supported of type int2, int4, int8, text and uuid!"),
+    }
+}
+
+fn key_type_name(value_type: &Type) -> &'static str {
+    match value_type {
+        Type::Int2 => "int2",
+        Type::Int4 => "int4",
+        Type::Int8 => "int8",
+        Type::Uuid => "uuid",
+        _ => "text",
+    }
+}
+
+/// A random version 4 UUID or, with a `time_range`, a version 7 UUID of a random time within it
+fn rand_uuid(rng: &mut impl Rng, time_range: Option<(i64, i64)>) -> uuid::Uuid {
+    match time_range {
+        Some(time_range) => {
+            // Version 7 has no times before 1970
+            let millis = rng.gen_range(time_range.0..=time_range.1).max(0) as u64 / 1000;
+            uuid::Builder::from_unix_timestamp_millis(millis, &rng.gen()).into_uuid()
+        }
+        None => uuid::Builder::from_random_bytes(rng.gen()).into_uuid(),
+    }
+}
+
+fn rand_numeric(rng: &mut impl Rng, precision: Option<i32>, scale: Option<i32>, non_finite: bool) -> Numeric {
+    if non_finite && 0 == rng.gen_range(0..20) {
+        // Infinity only fits into a numeric without precision
+        return match rng.gen_range(0..3) {
+            0 if precision.is_none() => Numeric::Infinity,
+            1 if precision.is_none() => Numeric::NegativeInfinity,
+            _ => Numeric::NaN,
+        };
+    }
+
+    let (precision, scale) = match (precision, scale) {
+        (Some(precision), scale) => (precision, scale.unwrap_or(0)),
+        (None, _) => {
+            let precision = rng.gen_range(1..=30);
+            (precision, rng.gen_range(0..=precision.min(10)))
+        },
+    };
+
+    let len = rng.gen_range(0..=precision);
+    Numeric::Finite {
+        negative: 0 == rng.gen_range(0..10),
+        digits: (0..len).map(|_| char::from(b'0' + rng.gen_range(0..10))).collect(),
+        scale,
+    }
+}
+
+fn rand_float(rng: &mut impl Rng, non_finite: bool) -> f64 {
+    if non_finite && 0 == rng.gen_range(0..20) {
+        return *[f64::NAN, f64::INFINITY, f64::NEG_INFINITY].choose(rng).unwrap();
+    }
+
+    let v = match rng.gen_range(0..5) {
+        0 => 0.0,
+        1 => rand_int(rng) as f64,
+        _ => rng.gen::<f64>() * 10f64.powi(rng.gen_range(-10..10)),
+    };
+    if 0 == rng.gen_range(0..10) {-v} else {v}
+}
+
+/// A point in time between the two given microseconds since the Unix epoch
+fn rand_timestamp(rng: &mut impl Rng, time_range: (i64, i64)) -> OffsetDateTime {
+    OffsetDateTime::from_unix_timestamp_nanos(rng.gen_range(time_range.0..=time_range.1) as i128 * 1000).unwrap()
+}
+
+fn rand_time(rng: &mut impl Rng) -> Time {
+    Time::MIDNIGHT + time::Duration::microseconds(rng.gen_range(0..86_400_000_000))
+}
+
+fn rand_interval(rng: &mut impl Rng) -> Interval {
+    let interval = Interval {
+        months: if 0 == rng.gen_range(0..3) {rng.gen_range(0..24)} else {0},
+        days: if 0 == rng.gen_range(0..3) {rng.gen_range(0..31)} else {0},
+        microseconds: match rng.gen_range(0..3) {
+            0 => 0,
+            1 => rng.gen_range(0..3600) * 1_000_000,
+            _ => rng.gen_range(0..86_400_000_000),
+        },
+    };
+    if 0 == rng.gen_range(0..10) {
+        Interval {months: -interval.months, days: -interval.days, microseconds: -interval.microseconds}
+    } else {
+        interval
+    }
+}
+
+/// A random IPv4 or IPv6 address with a netmask. For `cidr`, the host bits are cleared.
+fn rand_inet(rng: &mut impl Rng, cidr: bool) -> Inet {
+    let mut octets = if 0 == rng.gen_range(0..2) {
+        rng.gen::<[u8; 4]>().to_vec()
+    } else {
+        rng.gen::<[u8; 16]>().to_vec()
+    };
+    let bits = octets.len() as u8 * 8;
+    let netmask = if !cidr && 0 != rng.gen_range(0..3) {bits} else {rng.gen_range(0..=bits)};
+
+    if cidr {
+        for (i, octet) in octets.iter_mut().enumerate() {
+            let keep = (netmask as i32 - i as i32 * 8).clamp(0, 8);
+            *octet &= !(0xffu16 >> keep) as u8;
+        }
+    }
+
+    let address = match <[u8; 4]>::try_from(octets.as_slice()) {
+        Ok(octets) => std::net::IpAddr::from(octets),
+        Err(_) => std::net::IpAddr::from(<[u8; 16]>::try_from(octets.as_slice()).unwrap()),
+    };
+    Inet {address, netmask}
+}
+
+/// A bit string of `length` bits or, if `varying`, of up to `length` bits
+fn rand_bit_string(rng: &mut impl Rng, length: i32, varying: bool) -> BitString {
+    let length = if varying {rng.gen_range(0..=length)} else {length};
+    let mut bytes = (0..(length + 7) / 8).map(|_| rng.gen::<u8>()).collect::<Vec<u8>>();
+    if length % 8 != 0 {
+        *bytes.last_mut().unwrap() &= 0xffu8 << (8 - length % 8);
+    }
+    BitString {length, bytes}
+}
+
+/// A string of `min` to `max` characters
+fn rand_str(rng: &mut impl Rng, min: usize, max: usize) -> String {
+    use rand::distributions::DistString;
+
+    let len = rng.gen_range(min..=max);
+
+    match rng.gen_range(0..10) {
+        0 => rng.sample_iter::<char, _>(rand::distributions::Standard)
+            .take(len)
+            .collect(),
+        _ => rand::distributions::Alphanumeric.sample_string(rng, len),
+    }
+}
+
+/// Splits `expression` at `separator` where it is neither in parentheses, brackets, string constants nor quoted
+/// identifiers
+fn split_top_level<'a>(expression: &'a str, separator: &str) -> Vec<&'a str> {
+    let mut parts = Vec::new();
+    let (mut depth, mut quote, mut start) = (0, None, 0);
+    let bytes = expression.as_bytes();
+
+    // Bytes of multi-byte characters are never ASCII, so they match neither a quote, a bracket nor the separator
+    let mut i = 0;
+    while i < bytes.len() {
+        match bytes[i] {
+            b'\'' | b'"' if quote.is_none() => quote = Some(bytes[i]),
+            b'\'' | b'"' if quote == Some(bytes[i]) => quote = None,
+            b'(' | b'[' if quote.is_none() => depth += 1,
+            b')' | b']' if quote.is_none() => depth -= 1,
+            _ if quote.is_none() && depth == 0 && bytes[i..].starts_with(separator.as_bytes()) => {
+                parts.push(&expression[start..i]);
+                i += separator.len();
+                start = i;
+                continue;
+            },
+            _ => (),
+        }
+        i += 1;
+    }
+    parts.push(&expression[start..]);
+
+    parts
+}
+
+/// Removes parentheses and casts like `::text` around `expression`
+fn strip_expression(expression: &str) -> &str {
+    let mut expression = expression.trim();
+    loop {
+        let (mut depth, mut quote) = (0, None);
+        let enclosed = expression.starts_with('(') && expression.ends_with(')') && expression.char_indices().all(|(i, c)| {
+            match c {
+                '\'' | '"' if quote.is_none() => quote = Some(c),
+                '\'' | '"' if quote == Some(c) => quote = None,
+                '(' if quote.is_none() => depth += 1,
+                ')' if quote.is_none() => depth -= 1,
+                _ => (),
+            }
+            depth > 0 || i == expression.len() - 1
+        });
+        if enclosed {
+            expression = expression[1..expression.len() - 1].trim();
+            continue;
+        }
+
+        let parts = split_top_level(expression, "::");
+        let cast = parts[parts.len() - 1];
+        let value = expression[..expression.len() - cast.len()].trim_end_matches("::").trim();
+        if parts.len() > 1 && split_top_level(value, " ").len() == 1 && cast.chars().all(|c| c.is_alphanumeric() || " _[](),\".".contains(c)) {
+            expression = value;
+        } else {
+            return expression;
+        }
+    }
+}
+
+/// The value of a number or string constant
+fn parse_literal(expression: &str) -> Option<String> {
+    let expression = strip_expression(expression);
+    if expression.len() >= 2 && expression.starts_with('\'') && expression.ends_with('\'') {
+        Some(expression[1..expression.len() - 1].replace("''", "'"))
+    } else {
+        let number = expression.replace(' ', "");
+        number.parse::<f64>().ok().map(|_| number)
+    }
+}
+
+/// Parses the conditions of a CHECK constraint on the columns `subjects` as `pg_get_constraintdef` prints it, `VALUE`
+/// being the subject of the checks of domains. Conditions that are not understood become `Unparsed` checks of all
+/// subjects.
+fn parse_checks(definition: &str, subjects: &[String]) -> Vec<(String, Check)> {
+    let expression = definition.trim()
+        .trim_start_matches("CHECK")
+        .trim_end_matches("NOT VALID");
+
+    let mut checks = Vec::new();
+    for condition in split_top_level(strip_expression(expression), " AND ") {
+        let condition = strip_expression(condition);
+        if split_top_level(condition, " AND ").len() > 1 {
+            checks.extend(parse_checks(condition, subjects));
+        } else if let Some(check) = parse_check(condition, subjects) {
+            checks.extend(check);
+        } else {
+            println!("Ignoring condition {} on {}", condition, subjects.join(", "));
+            checks.extend(subjects.iter().map(|subject| (subject.clone(), Check::Unparsed)));
+        }
+    }
+
+    checks
+}
+
+fn parse_check(condition: &str, subjects: &[String]) -> Option<Vec<(String, Check)>> {
+    let subject_of = |expression: &str| {
+        let expression = strip_expression(expression);
+        subjects.iter()
+            .find(|subject| expression == subject.as_str() || expression == quote_identifier(subject))
+            .cloned()
+    };
+    let length_of = |expression: &str| {
+        let expression = strip_expression(expression);
+        ["length(", "char_length(", "character_length("].iter()
+            .filter_map(|function| expression.strip_prefix(function))
+            .find_map(|argument| argument.strip_suffix(')').and_then(subject_of))
+    };
+
+    if split_top_level(condition, " OR ").len() > 1 {
+        return None;
+    }
+
+    if let Some(expression) = condition.strip_suffix(" IS NOT NULL") {
+        return subject_of(expression).map(|subject| vec![(subject, Check::NotNull)]);
+    }
+
+    for operator in [" >= ", " <= ", " <> ", " ~* ", " ~ ", " > ", " < ", " = "] {
+        let [left, right] = &split_top_level(condition, operator)[..] else {
+            continue;
+        };
+
+        if let (Some(left), Some(right)) = (subject_of(left), subject_of(right)) {
+            return match operator.trim() {
+                "<" | "<=" => Some(vec![(left, Check::Before(right))]),
+                ">" | ">=" => Some(vec![(right, Check::Before(left))]),
+                _ => None,
+            };
+        }
+
+        if operator == " = " && right.starts_with("ANY ") {
+            let subject = subject_of(left)?;
+            let array = strip_expression(&right[4..]);
+            let elements = array.strip_prefix("ARRAY[")?.strip_suffix(']')?;
+            return split_top_level(elements, ", ").into_iter()
+                .map(parse_literal)
+                .collect::<Option<Vec<String>>>()
+                .map(|values| vec![(subject, Check::OneOf(values))]);
+        }
+
+        // Comparisons are turned around to have the subject on the left
+        let (subject, operator, literal, length) = if let Some(subject) = subject_of(left) {
+            (subject, operator.trim(), parse_literal(right)?, false)
+        } else if let Some(subject) = length_of(left) {
+            (subject, operator.trim(), parse_literal(right)?, true)
+        } else {
+            let operator = match operator.trim() {">=" => "<=", "<=" => ">=", ">" => "<", "<" => ">", operator => operator};
+            if let Some(subject) = subject_of(right) {
+                (subject, operator, parse_literal(left)?, false)
+            } else {
+                (length_of(right)?, operator, parse_literal(left)?, true)
+            }
+        };
+
+        let checks = if length {
+            let length = literal.parse::<usize>().ok()?;
+            match operator {
+                ">=" => vec![Check::MinLength(length)],
+                ">" => vec![Check::MinLength(length + 1)],
+                "<=" => vec![Check::MaxLength(length)],
+                "<" => vec![Check::MaxLength(length.checked_sub(1)?)],
+                "=" => vec![Check::MinLength(length), Check::MaxLength(length)],
+                _ => return None,
+            }
+        } else {
+            match (operator, literal.parse::<f64>().ok()) {
+                ("~" | "~*", _) => vec![Check::Matches(literal)],
+                ("=", _) => vec![Check::OneOf(vec![literal])],
+                ("<>", _) => vec![Check::NotEqual(literal)],
+                (">=", Some(number)) => vec![Check::Minimum(number, true)],
+                (">", Some(number)) => vec![Check::Minimum(number, false)],
+                ("<=", Some(number)) => vec![Check::Maximum(number, true)],
+                ("<", Some(number)) => vec![Check::Maximum(number, false)],
+                _ => return None,
+            }
+        };
+
+        return Some(checks.into_iter().map(|check| (subject.clone(), check)).collect());
+    }
+
+    None
+}
+
+/// One part of a regular expression with how often it is repeated
+#[derive(Debug)]
+enum Pattern {
+    Chars(Vec<char>),
+    Group(Vec<Vec<(Pattern, usize, usize)>>),
+}
+
+/// Generates a string matching a simple regular expression: literals, `.`, classes like `[A-Z0-9_]`, `\d`, `\w`
+/// and `\s`, groups with `|` and the quantifiers `?`, `*`, `+` and `{n,m}`. Anchors are ignored. Returns `None` for
+/// anything else, like lookarounds or back references. Unbounded quantifiers repeat up to `repeat` times.
+fn rand_matching(rng: &mut impl Rng, pattern: &str, repeat: usize) -> Option<String> {
+    fn printable() -> Vec<char> {
+        (' '..='~').collect()
+    }
+
+    fn class(escape: char) -> Option<Vec<char>> {
+        let chars = match escape.to_ascii_lowercase() {
+            'd' => ('0'..='9').collect::<Vec<char>>(),
+            'w' => ('a'..='z').chain('A'..='Z').chain('0'..='9').chain(['_']).collect(),
+            's' => vec![' '],
+            _ => return None,
+        };
+        if escape.is_ascii_uppercase() {
+            Some(printable().into_iter().filter(|c| !chars.contains(c)).collect())
+        } else {
+            Some(chars)
+        }
+    }
+
+    fn alternatives(chars: &[char], i: &mut usize, repeat: usize) -> Option<Vec<Vec<(Pattern, usize, usize)>>> {
+        let mut parsed = vec![Vec::new()];
+        while *i < chars.len() && chars[*i] != ')' {
+            let c = chars[*i];
+            *i += 1;
+            let pattern = match c {
+                '^' | '$' => continue,
+                '|' => {
+                    parsed.push(Vec::new());
+                    continue;
+                },
+                '(' => {
+                    if chars.get(*i) == Some(&'?') {
+                        if chars.get(*i + 1) != Some(&':') {
+                            return None;
+                        }
+                        *i += 2;
+                    }
+                    let group = alternatives(chars, i, repeat)?;
+                    if chars.get(*i) != Some(&')') {
+                        return None;
+                    }
+                    *i += 1;
+                    Pattern::Group(group)
+                },
+                '[' => {
+                    let negated = chars.get(*i) == Some(&'^');
+                    if negated {
+                        *i += 1;
+                    }
+                    let mut members = Vec::new();
+                    let mut first = true;
+                    while *i < chars.len() && (chars[*i] != ']' || first) {
+                        first = false;
+                        let c = chars[*i];
+                        if c == '\\' {
+                            let escape = *chars.get(*i + 1)?;
+                            members.extend(class(escape).unwrap_or_else(|| vec![escape]));
+                            *i += 2;
+                        } else if chars.get(*i + 1) == Some(&'-') && chars.get(*i + 2).is_some_and(|end| *end != ']') {
+                            members.extend(c..=chars[*i + 2]);
+                            *i += 3;
+                        } else {
+                            members.push(c);
+                            *i += 1;
+                        }
+                    }
+                    *i += 1;
+                    if negated {
+                        Pattern::Chars(printable().into_iter().filter(|c| !members.contains(c)).collect())
+                    } else {
+                        Pattern::Chars(members)
+                    }
+                },
+                '.' => Pattern::Chars(('a'..='z').chain('A'..='Z').chain('0'..='9').collect()),
+                '\\' => {
+                    let escape = *chars.get(*i)?;
+                    *i += 1;
+                    if escape.is_ascii_digit() {
+                        return None;
+                    }
+                    Pattern::Chars(class(escape).unwrap_or_else(|| vec![escape]))
+                },
+                c => Pattern::Chars(vec![c]),
+            };
+
+            let quantifier = match chars.get(*i) {
+                Some('?') => Some((0, 1)),
+                Some('*') => Some((0, repeat)),
+                Some('+') => Some((1, repeat.max(1))),
+                Some('{') => {
+                    let end = chars[*i..].iter().position(|c| *c == '}')? + *i;
+                    let repetition = chars[*i + 1..end].iter().collect::<String>();
+                    let (min, max) = match repetition.split_once(',') {
+                        Some((min, "")) => (min.parse().ok()?, min.parse::<usize>().ok()?.max(repeat)),
+                        Some((min, max)) => (min.parse().ok()?, max.parse().ok()?),
+                        None => (repetition.parse().ok()?, repetition.parse().ok()?),
+                    };
+                    *i = end;
+                    Some((min, max))
+                },
+                _ => None,
+            };
+            let (min, max) = quantifier.unwrap_or((1, 1));
+            if quantifier.is_some() {
+                // Steps past the quantifier, which for `{n,m}` ends with the closing brace
+                *i += 1;
+                // Lazy and possessive quantifiers generate the same
+                if matches!(chars.get(*i), Some('?' | '+')) {
+                    *i += 1;
+                }
+            }
+            if min > max {
+                return None;
+            }
+
+            parsed.last_mut().unwrap().push((pattern, min, max));
+        }
+        Some(parsed)
+    }
+
+    fn generate(rng: &mut impl Rng, alternatives: &[Vec<(Pattern, usize, usize)>], out: &mut String) -> Option<()> {
+        for (pattern, min, max) in alternatives.choose(rng)? {
+            for _ in 0..rng.gen_range(*min..=*max) {
+                match pattern {
+                    Pattern::Chars(chars) => out.push(*chars.choose(rng)?),
+                    Pattern::Group(group) => generate(rng, group, out)?,
+                }
+            }
+        }
+        Some(())
+    }
+
+    let chars = pattern.chars().collect::<Vec<char>>();
+    let mut i = 0;
+    let pattern = alternatives(&chars, &mut i, repeat)?;
+    if i != chars.len() {
+        return None;
+    }
+
+    let mut out = String::new();
+    generate(rng, &pattern, &mut out)?;
+    Some(out)
+}
+
+/// Whether `checked_value` turns text into values of `value_type`, other than by sending the text as is
+fn converts_text(value_type: &Type) -> bool {
+    matches!(value_type, Type::Int2 | Type::Int4 | Type::Int8 | Type::Text(_) | Type::Float4 | Type::Float8 | Type::Numeric {..} | Type::Enum(_))
+}
+
+/// Turns the text of a value satisfying checks into a value of `value_type`. Values of key types are also returned
+/// as key.
+fn checked_value(value_type: &Type, text: &str) -> (Box<dyn postgres::types::ToSql + Sync>, Option<Value>) {
+    match value_type {
+        Type::Int2 | Type::Int4 | Type::Int8 | Type::Text(_) => {
+            let value = match value_type {
+                Type::Int2 => Value::Int2(text.parse().unwrap()),
+                Type::Int4 => Value::Int4(text.parse().unwrap()),
+                Type::Int8 => Value::Int8(text.parse().unwrap()),
+                _ => Value::Text(text.to_string()),
+            };
+            (Box::new(value.clone()), Some(value))
+        },
+        Type::Float4 => (Box::new(text.parse::<f32>().unwrap()), None),
+        Type::Float8 => (Box::new(text.parse::<f64>().unwrap()), None),
+        Type::Numeric {..} => {
+            let (integer, fraction) = text.trim_start_matches('-').split_once('.').unwrap_or((text.trim_start_matches('-'), ""));
+            (Box::new(Numeric::Finite {negative: text.starts_with('-'), digits: format!("{}{}", integer, fraction), scale: fraction.len() as i32}), None)
+        },
+        _ => (Box::new(TypedString {value: text.to_string()}), None),
+    }
+}
+
+impl Database {
+    fn resolve_table(&self, name: &str) -> TableName {
+        if let Some((schema, table)) = name.split_once('.') {
+            let table_name = TableName {schema: schema.to_string(), name: table.to_string()};
+            if !self.tables.contains_key(&table_name) {
+                panic!("Table {} not found", table_name);
+            }
+            table_name
+        } else {
+            let mut candidates = self.table_names.iter().filter(|table_name| table_name.name == name);
+            match (candidates.next(), candidates.next()) {
+                (Some(table_name), None) => table_name.clone(),
+                (None, _) => panic!("Table {} not found", name),
+                (Some(_), Some(_)) => panic!("Table name {} is ambiguous, please qualify it with a schema, e.g. 'schema.{}'", name, name),
+            }
+        }
+    }
+
+    fn dependency_order(&self) -> Vec<TableName> {
+        let mut order = Vec::<TableName>::new();
+        let mut remaining = self.table_names.iter().cloned().collect::<BTreeSet<TableName>>();
+
+        while !remaining.is_empty() {
+            let ready = remaining.iter()
+                .filter(|table| self.tables[*table].foreign_keys.iter().all(|foreign_key| &foreign_key.table == *table || !remaining.contains(&foreign_key.table)))
+                .cloned()
+                .collect::<Vec<TableName>>();
+
+            if ready.is_empty() {
+                order.extend(remaining);
+                break;
+            }
+
+            for table in ready {
+                remaining.remove(&table);
+                order.push(table);
+            }
+        }
+
+        order
+    }
+
+    fn references(&self, from: &TableName, to: &TableName) -> bool {
+        let mut seen = BTreeSet::<&TableName>::new();
+        let mut todo = vec![from];
+
+        while let Some(table) = todo.pop() {
+            if table == to {
+                return true;
+            }
+            if seen.insert(table) {
+                if let Some(table_info) = self.tables.get(table) {
+                    todo.extend(table_info.foreign_keys.iter().map(|foreign_key| &foreign_key.table));
+                }
+            }
+        }
+
+        false
+    }
+
+    fn mark_cyclic_foreign_keys(&mut self) {
+        let cyclic = self.tables.values()
+            .map(|table| table.foreign_keys.iter().map(|foreign_key| self.references(&foreign_key.table, &table.name)).collect())
+            .collect::<Vec<Vec<bool>>>();
+
+        for (table, cyclic) in self.tables.values_mut().zip(cyclic) {
+            for (foreign_key, cyclic) in table.foreign_keys.iter_mut().zip(cyclic) {
+                foreign_key.cyclic = cyclic;
+                if cyclic {
+                    println!("Foreign key {} of table {} is part of a cycle", foreign_key.name, table.name);
+                }
+            }
+        }
+    }
+
+    /// `NOT NULL` foreign keys to other tables for which there are currently no keys to pick from.
+    fn unresolved_foreign_keys(&self, key_pool: &KeyPool, table: &TableName, set_column: Option<&str>) -> Vec<&ForeignKey> {
+        self.tables[table].foreign_keys.iter()
+            .filter(|foreign_key| !foreign_key.nullable && &foreign_key.table != table)
+            .filter(|foreign_key| set_column.is_none_or(|set_column| foreign_key.columns.iter().all(|(column, _)| column != set_column)))
+            .filter(|foreign_key| !key_pool.has_keys(&foreign_key.table, &foreign_key.referenced_columns()))
+            .collect()
+    }
+
+    fn missing_keys(&self, key_pool: &KeyPool, table: &TableName, set_column: Option<&str>) -> Vec<TableName> {
+        self.collect_missing_keys(key_pool, table, set_column, &mut BTreeSet::new(), &mut BTreeSet::new())
+    }
+
+    /// Follows the same steps as `insert_with_parents` without inserting anything: Tables that are reached through a
+    /// cyclic foreign key get a row in the same transaction, all other referenced tables need to contain rows already.
+    fn collect_missing_keys(&self, key_pool: &KeyPool, table: &TableName, set_column: Option<&str>, visiting: &mut BTreeSet<TableName>, inserted: &mut BTreeSet<TableName>) -> Vec<TableName> {
+        visiting.insert(table.clone());
+
+        let (deferred, before): (Vec<&ForeignKey>, Vec<&ForeignKey>) = self.unresolved_foreign_keys(key_pool, table, set_column)
+            .into_iter()
+            .partition(|foreign_key| foreign_key.deferrable);
+
+        let mut missing = Vec::new();
+        for foreign_key in before {
+            missing.extend(self.collect_missing_parent(key_pool, foreign_key, visiting, inserted));
+        }
+        inserted.insert(table.clone());
+        for foreign_key in deferred {
+            missing.extend(self.collect_missing_parent(key_pool, foreign_key, visiting, inserted));
+        }
+
+        missing
+    }
+
+    fn collect_missing_parent(&self, key_pool: &KeyPool, foreign_key: &ForeignKey, visiting: &mut BTreeSet<TableName>, inserted: &mut BTreeSet<TableName>) -> Vec<TableName> {
+        if inserted.contains(&foreign_key.table) {
+            Vec::new()
+        } else if foreign_key.cyclic && !visiting.contains(&foreign_key.table) {
+            self.collect_missing_keys(key_pool, &foreign_key.table, None, visiting, inserted)
+        } else {
+            vec![foreign_key.table.clone()]
+        }
+    }
+
+    /// Inserts a row into `table` and breaks foreign key cycles on the way: Tables referenced by a cyclic `NOT NULL`
+    /// foreign key without keys get a row in the same transaction, before the new row if the foreign key is checked
+    /// immediately, or afterwards if it is deferrable. In the latter case the new row is updated to reference the
+    /// parent row before the transaction ends. The rows of `table` come last in the returned rows.
+    #[allow(clippy::too_many_arguments)]
+    fn insert_with_parents(&self, rng: &mut impl Rng, client: &mut impl Connection, key_pool: &mut KeyPool, table: &TableName, set_columns: &[Option<(&str, Option<Value>)>], return_column: Option<&str>, visiting: &mut BTreeSet<TableName>) -> Result<Vec<(TableName, Row)>, postgres::Error> {
+        visiting.insert(table.clone());
+        let set_name = set_columns.iter().flatten().map(|(set_name, _)| *set_name).next();
+
+        let mut rows = Vec::new();
+        for foreign_key in self.unresolved_foreign_keys(key_pool, table, set_name).into_iter().filter(|foreign_key| !foreign_key.deferrable) {
+            if foreign_key.cyclic && !visiting.contains(&foreign_key.table) && !key_pool.has_keys(&foreign_key.table, &foreign_key.referenced_columns()) {
+                rows.extend(self.insert_with_parents(rng, client, key_pool, &foreign_key.table, &[None], None, visiting)?);
+            }
+        }
+
+        let deferred = self.unresolved_foreign_keys(key_pool, table, set_name);
+        let new_rows = if self.copy && deferred.is_empty() && !self.tables[table].unparsed_checks() {
+            self.copy_into_table(rng, client, key_pool, table, set_columns, return_column)?
+        } else {
+            self.insert_in_table(rng, client, key_pool, table, set_columns, return_column)?
+        };
+        for row in &new_rows {
+            key_pool.add_pending(table, row);
+        }
+
+        for foreign_key in &deferred {
+            if foreign_key.cyclic && !visiting.contains(&foreign_key.table) && !key_pool.has_keys(&foreign_key.table, &foreign_key.referenced_columns()) {
+                rows.extend(self.insert_with_parents(rng, client, key_pool, &foreign_key.table, &[None], None, visiting)?);
+            }
+        }
+
+        // All deferred foreign keys of a row are set by one update, as an update moves the row to another ctid
+        for row in new_rows.iter().filter(|_| !deferred.is_empty()) {
+            let mut assignments = Vec::<(&str, Value)>::new();
+            for foreign_key in &deferred {
+                let values = key_pool.choose(rng, &foreign_key.table, &foreign_key.referenced_columns())
+                    .unwrap_or_else(|| panic!("No keys found in table {} for foreign key of table {}", foreign_key.table, table));
+                for ((column, _), value) in foreign_key.columns.iter().zip(values) {
+                    if !assignments.iter().any(|(assigned, _)| assigned == column) {
+                        assignments.push((column, value));
+                    }
+                }
+            }
+
+            let update = format!("UPDATE {} SET {} WHERE ctid = ${}::text::tid",
+                                 table.quoted(),
+                                 assignments.iter()
+                                     .enumerate()
+                                     .map(|(i, (column, _))| format!("{} = ${}", quote_identifier(column), i + 1))
+                                     .collect::<Vec<String>>()
+                                     .join(", "),
+                                 assignments.len() + 1);
+            println!("{}", update);
+
+            let ctid = row["ctid"].clone().unwrap();
+            let update_vals = assignments.iter()
+                .map(|(_, value)| value)
+                .chain(std::iter::once(&ctid))
+                .map(|value| value as &(dyn postgres::types::ToSql + Sync))
+                .collect::<Vec<&(dyn postgres::types::ToSql + Sync)>>();
+            client.execute(&update, &update_vals[0..])?;
+        }
+
+        rows.extend(new_rows.into_iter().map(|row| (table.clone(), row)));
+        Ok(rows)
+    }
+
+    fn root_value(&self, rng: &mut impl Rng, client: &mut impl Connection, column: &Column) -> Result<Value, postgres::Error> {
+        if let Some(default) = &column.value_default {
+            Ok(client.query(&format!("select ({})::{}", default, key_type_name(&column.value_type)), &[])?
+               .into_iter()
+               .map(|row| row.get::<_, Value>(0))
+               .next()
+               .unwrap())
+        } else {
+            Ok(rand_key(rng, &column.value_type))
+        }
+    }
+
+    /// An explicit value for an identity column, a few values past the next one of its sequence. The sequence is moved
+    /// to the value, so that the rows that are left to their identity later don't collide with it.
+    fn identity_value(&self, rng: &mut impl Rng, client: &mut impl Connection, table: &TableName, column: &Column) -> Result<Value, postgres::Error> {
+        let sequence = format!("pg_get_serial_sequence('{}', '{}')", table.quoted().replace('\'', "''"), column.name.replace('\'', "''"));
+        Ok(client.query(&format!("select setval({sequence}, nextval({sequence}) + $1)::{}", key_type_name(&column.value_type)), &[&rng.gen_range(0..10i64)])?
+           .into_iter()
+           .map(|row| row.get::<_, Value>(0))
+           .next()
+           .unwrap())
+    }
+
+    /// Generates the values of a new row. Besides the data to insert, it returns the values of the columns that can be
+    /// used as keys, which are the columns of type int2, int4, int8, text and uuid that are not left to their default.
+    fn generate_row(&self, rng: &mut impl Rng, client: &mut impl Connection, key_pool: &KeyPool, table: &TableName, set_column: Option<(&str, Option<Value>)>, deferred: &[&ForeignKey]) -> Result<(InsertData, Row), postgres::Error> {
+        let mut data: InsertData = Vec::new();
+        let mut values = Row::new();
+
+        let table_info = &self.tables[table];
+
+        // Unique keys are only tracked if they have a column that can be generated again. Foreign keys are chosen
+        // again in every attempt, too.
+        let unique_keys = table_info.unique_keys.iter()
+            .filter(|columns| columns.iter().any(|column| set_column.as_ref().is_none_or(|(set_name, _)| set_name != column)))
+            .collect::<Vec<&Vec<String>>>();
+
+        for _ in 0..100 {
+            data.clear();
+            values.clear();
+
+            let mut foreign_values = BTreeMap::<&str, Option<Option<Value>>>::new();
+            for foreign_key in &table_info.foreign_keys {
+                if set_column.as_ref().is_some_and(|(set_name, _)| foreign_key.columns.iter().any(|(column, _)| column == set_name)) {
+                    continue;
+                }
+
+                if !foreign_key.has_key_type(table_info) {
+                    panic!("Foreign keys only supported of type int2, int4, int8, text and uuid!");
+                }
+
+                let all_default = foreign_key.columns.iter().all(|(column, _)| table_info.columns[column].value_default.is_some());
+                let self_referencing = &foreign_key.table == table;
+
+                if deferred.iter().any(|deferred| std::ptr::eq(*deferred, foreign_key)) {
+                    // Placeholder until the referenced row is inserted later in this transaction
+                    for (column, _) in &foreign_key.columns {
+                        foreign_values.entry(column).or_insert(Some(Some(rand_key(rng, &table_info.columns[column].value_type))));
+                    }
+                } else if (foreign_key.nullable && 0 == rng.gen_range(0..3)) || (all_default && 0 != rng.gen_range(0..3)) {
+                    for (column, _) in &foreign_key.columns {
+                        foreign_values.entry(column).or_insert(None);
+                    }
+                } else if let Some(values) = if self_referencing {key_pool.choose_parent(rng, table, foreign_key)} else {key_pool.choose(rng, &foreign_key.table, &foreign_key.referenced_columns())} {
+                    for ((column, _), value) in foreign_key.columns.iter().zip(values) {
+                        foreign_values.entry(column).or_insert(Some(Some(value)));
+                    }
+                } else if foreign_key.nullable {
+                    for (column, _) in &foreign_key.columns {
+                        foreign_values.entry(column).or_insert(Some(None));
+                    }
+                } else if self_referencing {
+                    // New root row that references itself
+                    for (column, fcolumn) in &foreign_key.columns {
+                        let value = match foreign_values.get(fcolumn.as_str()) {
+                            Some(Some(Some(value))) => value.clone(),
+                            _ => self.root_value(rng, client, &table_info.columns[fcolumn])?,
+                        };
+                        foreign_values.insert(fcolumn, Some(Some(value.clone())));
+                        foreign_values.entry(column).or_insert(Some(Some(value)));
+                    }
+                } else {
+                    panic!("No keys found in table {} for foreign key of table {}", foreign_key.table, table);
+                }
+            }
+
+            // Columns that have to be less than another column get the lower of two values, the other column the
+            // upper one
+            let mut ordered = BTreeMap::<&str, Box<dyn postgres::types::ToSql + Sync>>::new();
+            for (column, other) in table_info.columns.values().flat_map(|column| column.checks.iter().filter_map(move |check| match check {
+                Check::Before(other) => Some((column, &table_info.columns[other])),
+                _ => None,
+            })) {
+                let generated = |column: &Column| !column.generated && !column.identity_always
+                    && !foreign_values.contains_key(column.name.as_str())
+                    && set_column.as_ref().is_none_or(|(set_name, _)| *set_name != column.name)
+                    && !ordered.contains_key(column.name.as_str())
+                    && column.checks.iter().all(|check| matches!(check, Check::Before(_) | Check::NotNull | Check::Unparsed));
+                if orderable(&column.value_type, &other.value_type) && generated(column) && generated(other) {
+                    let (lower, upper) = self.rand_bounds(rng, &column.value_type);
+                    ordered.insert(&column.name, lower);
+                    ordered.insert(&other.name, upper);
+                }
+            }
+
+            let mut counter = 0;
+            for column_info in table_info.column_names.iter().map(|column| &table_info.columns[column]) {
+                let column = &column_info.name;
+                println!("  {}", column);
+
+                if column_info.generated || (column_info.identity_always && !self.overriding_system_value) {
+                    data.push((column.clone(), None));
+                } else if let Some((_, value)) = set_column.as_ref().filter(|(set_name, _)| set_name == column) {
+                    counter += 1;
+                    values.insert(column.clone(), value.clone());
+                    data.push((column.clone(), Some((counter, Box::new(value.clone()), column_info.type_specifier.clone()))));
+                } else if let Some(foreign_value) = foreign_values.get(column.as_str()) {
+                    if let Some(value) = foreign_value {
+                        counter += 1;
+                        values.insert(column.clone(), value.clone());
+                        data.push((column.clone(), Some((counter, Box::new(value.clone()), column_info.type_specifier.clone()))));
+                    } else {
+                        data.push((column.clone(), None));
+                    }
+                } else if column_info.identity && self.overriding_system_value && !self.copy && 0 == rng.gen_range(0..3) {
+                    let value = self.identity_value(rng, client, table, column_info)?;
+                    counter += 1;
+                    values.insert(column.clone(), Some(value.clone()));
+                    data.push((column.clone(), Some((counter, Box::new(value), column_info.type_specifier.clone()))));
+                } else if (column_info.value_nullable && 0 == rng.gen_range(0..3)) || (column_info.value_default.is_some() && !ordered.contains_key(column.as_str()) && (column_info.primary_key || 0 != rng.gen_range(0..3))) {
+                    data.push((column.clone(), None));
+                } else if let Some(value) = ordered.remove(column.as_str()) {
+                    counter += 1;
+                    data.push((column.clone(), Some((counter, value, column_info.type_specifier.clone()))));
+                } else if let Some(Check::OneOf(constants)) = column_info.checks.iter().find(|check| matches!(check, Check::OneOf(_))).filter(|_| !converts_text(&column_info.value_type)) {
+                    // Constants of other types are sent as text for the database to cast
+                    let constant = constants.choose(rng).unwrap().clone();
+                    if matches!(column_info.value_type, Type::Uuid) {
+                        values.insert(column.clone(), uuid::Uuid::parse_str(&constant).ok().map(Value::Uuid));
+                    }
+                    counter += 1;
+                    data.push((column.clone(), Some((counter, Box::new(constant), Some(format!("text::{}", column_info.type_name))))));
+                } else if let Some((value, key)) = self.rand_checked_value(rng, &column_info.value_type, &column_info.checks) {
+                    if key.is_some() {
+                        values.insert(column.clone(), key);
+                    }
+                    counter += 1;
+                    data.push((column.clone(), Some((counter, value, column_info.type_specifier.clone()))));
+                } else {
+                    let value: Box<dyn postgres::types::ToSql + Sync> =
+                    match &column_info.value_type {
+                        Type::Int2 | Type::Int4 | Type::Int8 | Type::Text(_) => {
+                            let value = match column_info.string_length {
+                                _ if column_info.unique => rand_unique_key(rng, &column_info.value_type, column_info.string_length),
+                                Some((min, max)) => Value::Text(rand_str(rng, min, max)),
+                                None => rand_key(rng, &column_info.value_type),
+                            };
+                            values.insert(column.clone(), Some(value.clone()));
+                            Box::new(value)
+                        },
+                        Type::Uuid => {
+                            let value = Value::Uuid(rand_uuid(rng, self.uuid_v7.then_some(self.time_range)));
+                            values.insert(column.clone(), Some(value.clone()));
+                            Box::new(value)
+                        },
+                        Type::Json | Type::Jsonb if column_info.json_schema.is_some() => Box::new(self.rand_json_schema(rng, column_info.json_schema.as_ref().unwrap())),
+                        _ => self.rand_value(rng, &column_info.value_type),
+                    };
+
+                    counter += 1;
+                    data.push((column.clone(), Some((counter, value, column_info.type_specifier.clone()))));
+                }
+            }
+
+            if key_pool.claim_unique(table, &unique_keys, &values) {
+                break;
+            }
+        }
+
+        Ok((data, values))
+    }
+
+    /// The text of a value of `value_type` that satisfies `checks`, or `None` if there is nothing to satisfy for this
+    /// type.
+    fn rand_checked(&self, rng: &mut impl Rng, value_type: &Type, checks: &[Check]) -> Option<String> {
+        let not_equal = |text: &String| !checks.iter().any(|check| matches!(check, Check::NotEqual(value) if value == text));
+
+        for check in checks.iter().filter(|_| converts_text(value_type)) {
+            if let Check::OneOf(values) = check {
+                return values.iter().filter(|value| not_equal(value)).collect::<Vec<&String>>().choose(rng).map(|value| value.to_string());
+            }
+        }
+
+        let minimum = checks.iter()
+            .filter_map(|check| if let Check::Minimum(minimum, inclusive) = check {Some((*minimum, *inclusive))} else {None})
+            .max_by(|a, b| a.0.total_cmp(&b.0));
+        let maximum = checks.iter()
+            .filter_map(|check| if let Check::Maximum(maximum, inclusive) = check {Some((*maximum, *inclusive))} else {None})
+            .min_by(|a, b| a.0.total_cmp(&b.0));
+
+        // Numbers are generated as integers in units of the last digit
+        let scale = match value_type {
+            Type::Int2 | Type::Int4 | Type::Int8 => 0,
+            Type::Numeric {scale, ..} => scale.unwrap_or(2).max(0),
+            Type::Float4 | Type::Float8 => 3,
+            Type::Text(max_length) => {
+                let pattern = checks.iter().find_map(|check| if let Check::Matches(pattern) = check {Some(pattern)} else {None});
+                let min_length = checks.iter().filter_map(|check| if let Check::MinLength(length) = check {Some(*length)} else {None}).max();
+                let max_check = checks.iter().filter_map(|check| if let Check::MaxLength(length) = check {Some(*length)} else {None}).min();
+                if pattern.is_none() && min_length.is_none() && max_check.is_none() && checks.iter().all(|check| !matches!(check, Check::NotEqual(_))) {
+                    return None;
+                }
+
+                let min_length = min_length.unwrap_or(0);
+                let max_length = max_check.into_iter()
+                    .chain(max_length.map(|length| length as usize))
+                    .min()
+                    .unwrap_or(49.max(min_length));
+                for _ in 0..100 {
+                    let text = match pattern {
+                        Some(pattern) => match rand_matching(rng, pattern, max_length.clamp(1, 16)) {
+                            Some(text) => text,
+                            None => {
+                                println!("Can not generate values for pattern {}", pattern);
+                                rand_str(rng, min_length, max_length)
+                            },
+                        },
+                        None => rand_str(rng, min_length, max_length),
+                    };
+                    let length = text.chars().count();
+                    if length >= min_length && length <= max_length && not_equal(&text) {
+                        return Some(text);
+                    }
+                }
+                return None;
+            },
+            Type::Enum(values) if checks.iter().any(|check| matches!(check, Check::NotEqual(_))) => {
+                return values.iter().filter(|value| not_equal(value)).collect::<Vec<&String>>().choose(rng).map(|value| value.to_string());
+            },
+            _ => return None,
+        };
+        if minimum.is_none() && maximum.is_none() && checks.iter().all(|check| !matches!(check, Check::NotEqual(_))) {
+            return None;
+        }
+
+        let unit = 10f64.powi(scale);
+        let mut lower = minimum.map(|(minimum, inclusive)| {
+            let lower = (minimum * unit).ceil() as i64;
+            if !inclusive && lower as f64 == minimum * unit {lower + 1} else {lower}
+        });
+        let mut upper = maximum.map(|(maximum, inclusive)| {
+            let upper = (maximum * unit).floor() as i64;
+            if !inclusive && upper as f64 == maximum * unit {upper - 1} else {upper}
+        });
+        let (type_lower, type_upper) = match value_type {
+            Type::Int2 => (i16::MIN as i64, i16::MAX as i64),
+            Type::Int4 => (i32::MIN as i64, i32::MAX as i64),
+            Type::Numeric {precision: Some(precision), ..} => {
+                let bound = 10i64.saturating_pow((*precision as u32).min(18)) - 1;
+                (-bound, bound)
+            },
+            _ => (i64::MIN / 2, i64::MAX / 2),
+        };
+        lower = Some(lower.unwrap_or(type_lower).max(type_lower));
+        upper = Some(upper.unwrap_or(type_upper).min(type_upper));
+        let (lower, upper) = (lower.unwrap(), upper.unwrap());
+        if lower > upper {
+            return None;
+        }
+
+        for _ in 0..100 {
+            // Mostly close to one of the bounds, as the range can be huge
+            let units = match (minimum.is_some(), maximum.is_some()) {
+                (true, true) => rng.gen_range(lower..=upper),
+                (true, false) => lower.saturating_add((rand_int(rng).unsigned_abs() as i64).saturating_mul(unit as i64)).min(upper),
+                (false, true) => upper.saturating_sub((rand_int(rng).unsigned_abs() as i64).saturating_mul(unit as i64)).max(lower),
+                (false, false) => (rand_int(rng) as i64).saturating_mul(unit as i64).clamp(lower, upper),
+            };
+            let text = if scale == 0 {
+                units.to_string()
+            } else {
+                let digits = format!("{:0>width$}", units.unsigned_abs(), width = scale as usize + 1);
+                let (integer, fraction) = digits.split_at(digits.len() - scale as usize);
+                format!("{}{}.{}", if units < 0 {"-"} else {""}, integer, fraction)
+            };
+            if not_equal(&text) && text.parse::<f64>().ok().is_none_or(|number| checks.iter().all(|check| !matches!(check, Check::NotEqual(value) if value.parse::<f64>().ok() == Some(number)))) {
+                return Some(text);
+            }
+        }
+        None
+    }
+
+    /// A value satisfying `checks`, for arrays in all elements, or `None` if the generator of the type is sufficient.
+    /// Values of key types are also returned as key.
+    fn rand_checked_value(&self, rng: &mut impl Rng, value_type: &Type, checks: &[Check]) -> Option<(Box<dyn postgres::types::ToSql + Sync>, Option<Value>)> {
+        if checks.is_empty() {
+            return None;
+        }
+
+        if let Type::Array(element_type, dimensions) = value_type {
+            let dimensions = (0..*dimensions)
+                .map(|_| rng.gen_range(1..=self.array_length))
+                .collect::<Vec<i32>>();
+            let mut elements = Vec::new();
+            for _ in 0..dimensions.iter().product::<i32>() {
+                elements.push(if self.array_nulls && !checks.contains(&Check::NotNull) && 0 == rng.gen_range(0..5) {
+                    None
+                } else if let Some(text) = self.rand_checked(rng, element_type, checks) {
+                    Some(checked_value(element_type, &text).0)
+                } else {
+                    Some(self.rand_value(rng, element_type))
+                });
+            }
+            return Some((Box::new(ArrayValue {dimensions, elements}), None));
+        }
+
+        self.rand_checked(rng, value_type, checks)
+            .map(|text| checked_value(value_type, &text))
+    }
+
+    /// A random value of the given type. Values of key types are not remembered, see `generate_row` for that.
+    fn rand_value(&self, rng: &mut impl Rng, value_type: &Type) -> Box<dyn postgres::types::ToSql + Sync> {
+        match value_type {
+            Type::Bool => Box::new(0 == rng.gen_range(0..2)),
+            Type::Int2 | Type::Int4 | Type::Int8 | Type::Text(_) => Box::new(rand_key(rng, value_type)),
+            Type::Uuid => Box::new(Value::Uuid(rand_uuid(rng, self.uuid_v7.then_some(self.time_range)))),
+            Type::ByteArray => Box::new(Vec::<u8>::new()),
+            Type::Json | Type::Jsonb => Box::new(self.rand_json_object(rng, self.json_depth)),
+            Type::Date => Box::new(rand_timestamp(rng, self.time_range).date()),
+            Type::Time => Box::new(rand_time(rng)),
+            Type::TimeTz => Box::new(TimeTz {time: rand_time(rng), offset: UtcOffset::from_whole_seconds(rng.gen_range(-12..=14) * 3600).unwrap()}),
+            Type::Interval => Box::new(rand_interval(rng)),
+            Type::Timestamp => {
+                let timestamp = rand_timestamp(rng, self.time_range);
+                Box::new(PrimitiveDateTime::new(timestamp.date(), timestamp.time()))
+            },
+            Type::TimestampTz => Box::new(rand_timestamp(rng, self.time_range)),
+            Type::Numeric {precision, scale} => Box::new(rand_numeric(rng, *precision, *scale, self.non_finite)),
+            Type::Float4 => Box::new(rand_float(rng, self.non_finite) as f32),
+            Type::Float8 => Box::new(rand_float(rng, self.non_finite)),
+            Type::Inet => Box::new(rand_inet(rng, false)),
+            Type::Cidr => Box::new(rand_inet(rng, true)),
+            Type::MacAddr => Box::new(MacAddr {bytes: rng.gen::<[u8; 6]>().to_vec()}),
+            Type::MacAddr8 => Box::new(MacAddr {bytes: rng.gen::<[u8; 8]>().to_vec()}),
+            Type::Bit(length) => Box::new(rand_bit_string(rng, *length, false)),
+            Type::VarBit(length) => Box::new(rand_bit_string(rng, length.unwrap_or(64), true)),
+            Type::Enum(values) => Box::new(TypedString {value: values.choose(rng).unwrap().clone()}),
+            Type::Range(subtype) => Box::new(self.rand_range(rng, subtype)),
+            Type::MultiRange(subtype) => Box::new(MultiRange {
+                ranges: (0..rng.gen_range(0..=3)).map(|_| self.rand_range(rng, subtype)).collect(),
+            }),
+            Type::Composite(attributes) => Box::new(CompositeValue {
+                fields: attributes.iter()
+                    .map(|(_, attribute_type, checks)| {
+                        if !checks.contains(&Check::NotNull) && 0 == rng.gen_range(0..10) {
+                            None
+                        } else if let Some((value, _)) = self.rand_checked_value(rng, attribute_type, checks) {
+                            Some(value)
+                        } else {
+                            Some(self.rand_value(rng, attribute_type))
+                        }
+                    })
+                    .collect(),
+            }),
+            Type::Array(element_type, dimensions) => {
+                let dimensions = (0..*dimensions)
+                    .map(|_| rng.gen_range(1..=self.array_length))
+                    .collect::<Vec<i32>>();
+                let elements = (0..dimensions.iter().product::<i32>())
+                    .map(|_| if self.array_nulls && 0 == rng.gen_range(0..5) {None} else {Some(self.rand_value(rng, element_type))})
+                    .collect();
+                Box::new(ArrayValue {dimensions, elements})
+            },
+        }
+    }
+
+    /// A range of the given subtype, sometimes empty or unbounded
+    fn rand_range(&self, rng: &mut impl Rng, subtype: &Type) -> RangeValue {
+        if 0 == rng.gen_range(0..10) {
+            return RangeValue::Empty;
+        }
+
+        let (lower, upper) = self.rand_bounds(rng, subtype);
+        RangeValue::Bounds {
+            lower: if 0 == rng.gen_range(0..10) {None} else {Some((lower, 0 != rng.gen_range(0..2)))},
+            upper: if 0 == rng.gen_range(0..10) {None} else {Some((upper, 0 != rng.gen_range(0..2)))},
+        }
+    }
+
+    /// Two values of the given type, the lower one first. The values differ unless the type has hardly any values.
+    fn rand_bounds(&self, rng: &mut impl Rng, value_type: &Type) -> (Box<dyn postgres::types::ToSql + Sync>, Box<dyn postgres::types::ToSql + Sync>) {
+        fn sorted<T: PartialOrd>(mut generate: impl FnMut() -> T) -> (T, T) {
+            let (a, mut b) = (generate(), generate());
+            for _ in 0..10 {
+                if a != b {
+                    break;
+                }
+                b = generate();
+            }
+            if a <= b {(a, b)} else {(b, a)}
+        }
+
+        match value_type {
+            Type::Int2 => {
+                let (lower, upper) = sorted(|| (rand_int(rng) % 32768) as i16);
+                (Box::new(lower), Box::new(upper))
+            },
+            Type::Int4 => {
+                let (lower, upper) = sorted(|| rand_int(rng));
+                (Box::new(lower), Box::new(upper))
+            },
+            Type::Int8 => {
+                let (lower, upper) = sorted(|| rand_int(rng) as i64);
+                (Box::new(lower), Box::new(upper))
+            },
+            Type::Numeric {precision, scale} => {
+                // At most hundredths, to have bounds with a fractional part that are easy to compare, and no more
+                // digits than the precision allows
+                let digits = scale.map_or(2, |scale| scale.clamp(0, 2));
+                let bound = precision.map_or(i64::MAX, |precision| 10i64.saturating_pow((precision - scale.unwrap_or(0) + digits).clamp(0, 18) as u32) - 1);
+                let unit = 10i64.pow(digits as u32);
+                let (lower, upper) = sorted(|| (rand_int(rng) as i64 * unit + rng.gen_range(0..unit)).clamp(-bound, bound));
+                let numeric = |value: i64| Numeric::Finite {negative: value < 0, digits: value.unsigned_abs().to_string(), scale: digits};
+                (Box::new(numeric(lower)), Box::new(numeric(upper)))
+            },
+            Type::Float4 => {
+                let (lower, upper) = sorted(|| rand_float(rng, false) as f32);
+                (Box::new(lower), Box::new(upper))
+            },
+            Type::Float8 => {
+                let (lower, upper) = sorted(|| rand_float(rng, false));
+                (Box::new(lower), Box::new(upper))
+            },
+            Type::Date => {
+                let (lower, upper) = sorted(|| rand_timestamp(rng, self.time_range).date());
+                (Box::new(lower), Box::new(upper))
+            },
+            Type::Timestamp => {
+                let (lower, upper) = sorted(|| rand_timestamp(rng, self.time_range));
+                (Box::new(PrimitiveDateTime::new(lower.date(), lower.time())), Box::new(PrimitiveDateTime::new(upper.date(), upper.time())))
+            },
+            Type::TimestampTz => {
+                let (lower, upper) = sorted(|| rand_timestamp(rng, self.time_range));
+                (Box::new(lower), Box::new(upper))
+            },
+            _ => panic!("Unexpected RANGE type: {:?}", value_type),
+        }
+    }
+
+    /// A JSON object with up to `json_keys` members, nested up to `depth` levels
+    fn rand_json_object(&self, rng: &mut impl Rng, depth: u32) -> serde_json::Value {
+        use rand::distributions::DistString;
+
+        (0..rng.gen_range(0..=self.json_keys))
+            .map(|_| {
+                let key_length = rng.gen_range(1..10);
+                (rand::distributions::Alphanumeric.sample_string(rng, key_length), self.rand_json(rng, depth.saturating_sub(1)))
+            })
+            .collect::<serde_json::Map<String, serde_json::Value>>()
+            .into()
+    }
+
+    fn rand_json(&self, rng: &mut impl Rng, depth: u32) -> serde_json::Value {
+        use rand::distributions::DistString;
+
+        match rng.gen_range(0..if depth > 0 {8} else {6}) {
+            0 => serde_json::Value::Null,
+            1 => (0 == rng.gen_range(0..2)).into(),
+            2 => rand_int(rng).into(),
+            3 => rand_float(rng, false).into(),
+            4 | 5 => {
+                // jsonb can not hold NUL characters, so no arbitrary strings here
+                let len = rng.gen_range(0..50);
+                rand::distributions::Alphanumeric.sample_string(rng, len).into()
+            },
+            6 => (0..rng.gen_range(0..=self.json_keys))
+                .map(|_| self.rand_json(rng, depth - 1))
+                .collect(),
+            _ => self.rand_json_object(rng, depth),
+        }
+    }
+
+    /// A JSON document matching `schema`. Supports `type`, `enum`, `const`, `anyOf`, `oneOf`, `allOf`, `properties`,
+    /// `required`, `items`, `minItems`, `maxItems`, `minimum`, `maximum`, `minLength`, `maxLength` and the formats
+    /// `date-time`, `date`, `uuid` and `email`.
+    fn rand_json_schema(&self, rng: &mut impl Rng, schema: &serde_json::Value) -> serde_json::Value {
+        use rand::distributions::DistString;
+        use serde_json::Value as Json;
+
+        let schema = match schema {
+            Json::Object(schema) => schema,
+            Json::Bool(false) => return Json::Null,
+            _ => return self.rand_json(rng, self.json_depth),
+        };
+
+        if let Some(value) = schema.get("const") {
+            return value.clone();
+        }
+        if let Some(Json::Array(values)) = schema.get("enum") {
+            return values.choose(rng).cloned().unwrap_or(Json::Null);
+        }
+        if let Some(Json::Array(schemas)) = schema.get("anyOf").or_else(|| schema.get("oneOf")) {
+            if let Some(schema) = schemas.choose(rng) {
+                return self.rand_json_schema(rng, schema);
+            }
+        }
+        if let Some(Json::Array(schemas)) = schema.get("allOf") {
+            let mut merged = schema.clone();
+            merged.remove("allOf");
+            for part in schemas.iter().filter_map(Json::as_object) {
+                for (key, value) in part {
+                    match (merged.get_mut(key), value) {
+                        (Some(Json::Object(properties)), Json::Object(more)) if key == "properties" => properties.extend(more.clone()),
+                        (Some(Json::Array(required)), Json::Array(more)) if key == "required" => required.extend(more.clone()),
+                        (Some(_), _) => (),
+                        (None, _) => {merged.insert(key.clone(), value.clone());},
+                    }
+                }
+            }
+            return self.rand_json_schema(rng, &Json::Object(merged));
+        }
+
+        let types = match schema.get("type") {
+            Some(Json::String(value_type)) => vec![value_type.as_str()],
+            Some(Json::Array(types)) => types.iter().filter_map(Json::as_str).collect(),
+            _ if schema.contains_key("properties") || schema.contains_key("required") => vec!["object"],
+            _ if schema.contains_key("items") => vec!["array"],
+            _ if schema.contains_key("minimum") || schema.contains_key("maximum") => vec!["number"],
+            _ if schema.contains_key("minLength") || schema.contains_key("maxLength") || schema.contains_key("format") => vec!["string"],
+            _ => return self.rand_json(rng, self.json_depth),
+        };
+
+        let minimum = schema.get("minimum").and_then(Json::as_f64);
+        let maximum = schema.get("maximum").and_then(Json::as_f64);
+        let length = |rng: &mut _, min_key, max_key, default_max| {
+            let min = schema.get(min_key).and_then(Json::as_u64).unwrap_or(0);
+            let max = schema.get(max_key).and_then(Json::as_u64).unwrap_or(min.max(default_max));
+            Rng::gen_range(rng, min..=max.max(min)) as usize
+        };
+
+        match types.choose(rng).copied().unwrap_or("null") {
+            "boolean" => (0 == rng.gen_range(0..2)).into(),
+            "integer" => match (minimum, maximum) {
+                (None, None) => rand_int(rng).into(),
+                (minimum, maximum) => {
+                    let minimum = minimum.map_or(maximum.unwrap() as i64 - 1000, |minimum| minimum.ceil() as i64);
+                    let maximum = maximum.map_or(minimum + 1000, |maximum| maximum.floor() as i64);
+                    rng.gen_range(minimum..=maximum.max(minimum)).into()
+                },
+            },
+            "number" => match (minimum, maximum) {
+                (None, None) => rand_float(rng, false).into(),
+                (minimum, maximum) => {
+                    let minimum = minimum.unwrap_or(maximum.unwrap() - 1000.0);
+                    let maximum = maximum.unwrap_or(minimum + 1000.0).max(minimum);
+                    rng.gen_range(minimum..=maximum).into()
+                },
+            },
+            "string" => match schema.get("format").and_then(Json::as_str) {
+                Some(format @ ("date-time" | "date")) => {
+                    let timestamp = rand_timestamp(rng, self.time_range);
+                    let date = format!("{:04}-{:02}-{:02}", timestamp.year(), timestamp.month() as u8, timestamp.day());
+                    if format == "date" {
+                        date.into()
+                    } else {
+                        format!("{}T{:02}:{:02}:{:02}Z", date, timestamp.hour(), timestamp.minute(), timestamp.second()).into()
+                    }
+                },
+                Some("uuid") => rand_uuid(rng, self.uuid_v7.then_some(self.time_range)).to_string().into(),
+                Some("email") => {
+                    let (user, domain) = (rng.gen_range(1..20), rng.gen_range(1..20));
+                    format!("{}@{}.com", rand::distributions::Alphanumeric.sample_string(rng, user), rand::distributions::Alphanumeric.sample_string(rng, domain)).into()
+                },
+                _ => {
+                    let len = length(rng, "minLength", "maxLength", 20);
+                    rand::distributions::Alphanumeric.sample_string(rng, len).into()
+                },
+            },
+            "array" => match schema.get("items") {
+                Some(Json::Array(items)) => items.iter()
+                    .map(|item| self.rand_json_schema(rng, item))
+                    .collect(),
+                items => (0..length(rng, "minItems", "maxItems", self.json_keys as u64))
+                    .map(|_| self.rand_json_schema(rng, items.unwrap_or(&Json::Bool(true))))
+                    .collect(),
+            },
+            "object" => match schema.get("properties").and_then(Json::as_object) {
+                Some(properties) => {
+                    let required = schema.get("required").and_then(Json::as_array).cloned().unwrap_or_default();
+                    let mut object = serde_json::Map::new();
+                    for (key, property) in properties {
+                        if required.iter().any(|required| required == key) || 0 != rng.gen_range(0..3) {
+                            object.insert(key.clone(), self.rand_json_schema(rng, property));
+                        }
+                    }
+                    object.into()
+                },
+                None => self.rand_json_object(rng, self.json_depth),
+            },
+            _ => Json::Null,
+        }
+    }
+
+    fn return_columns(&self, table: &TableName, return_column: Option<&str>) -> Vec<String> {
+        let table_info = &self.tables[table];
+
+        let mut return_columns = table_info.referenced_keys.iter()
+            .flatten()
+            .chain(table_info.foreign_keys.iter()
+                   .filter(|foreign_key| &foreign_key.table == table)
+                   .flat_map(|foreign_key| foreign_key.columns.iter().map(|(column, _)| column)))
+            .chain(return_column.map(str::to_string).iter())
+            .cloned()
+            .collect::<Vec<String>>();
+        return_columns.sort();
+        return_columns.dedup();
+        return_columns
+    }
+
+    /// Like `insert_in_table`, but streams the rows with a binary `COPY`. Columns with a default are left out, unless
+    /// their values are needed as keys. In that case, the defaults are calculated before and sent with the rows.
+    /// Generated columns are always left out.
+    fn copy_into_table(&self, rng: &mut impl Rng, client: &mut impl Connection, key_pool: &KeyPool, table: &TableName, set_columns: &[Option<(&str, Option<Value>)>], return_column: Option<&str>) -> Result<Vec<Row>, postgres::Error> {
+        let table_info = &self.tables[table];
+        let return_columns = self.return_columns(table, return_column);
+        let set_name = set_columns.iter().flatten().map(|(set_name, _)| *set_name).next();
+
+        let columns = table_info.column_names.iter()
+            .filter(|column| !table_info.columns[*column].generated)
+            .filter(|column| table_info.columns[*column].value_default.is_none()
+                    || return_columns.contains(column)
+                    || set_name == Some(column.as_str())
+                    || table_info.foreign_keys.iter().any(|foreign_key| foreign_key.columns.iter().any(|(fk_column, _)| fk_column == *column)))
+            .cloned()
+            .collect::<Vec<String>>();
+
+        let default_columns = columns.iter()
+            .filter(|column| table_info.columns[*column].value_default.is_some())
+            .cloned()
+            .collect::<Vec<String>>();
+        let defaults = if default_columns.is_empty() {
+            Vec::new()
+        } else {
+            let default_expressions = default_columns.iter()
+                .map(|column| &table_info.columns[column])
+                .map(|column_info| format!("({})::{}", column_info.value_default.as_ref().unwrap(), key_type_name(&column_info.value_type)))
+                .collect::<Vec<String>>();
+            client.query(&format!("select {} from generate_series(1, {})", default_expressions.join(", "), set_columns.len()), &[])?
+                .into_iter()
+                .map(|row| default_columns.iter()
+                     .enumerate()
+                     .map(|(i, column)| (column.clone(), row.get::<_, Option<Value>>(i)))
+                     .collect::<Row>())
+                .collect::<Vec<Row>>()
+        };
+
+        let mut rows = Vec::<Vec<Box<dyn postgres::types::ToSql + Sync>>>::new();
+        let mut new_rows = Vec::<Row>::new();
+        for (i, set_column) in set_columns.iter().enumerate() {
+            let (data, mut values) = self.generate_row(rng, client, key_pool, table, set_column.clone(), &[])?;
+
+            let mut row = Vec::<Box<dyn postgres::types::ToSql + Sync>>::new();
+            for (column, idval) in data.into_iter().filter(|(column, _)| columns.contains(column)) {
+                row.push(match (idval, defaults.get(i).and_then(|defaults| defaults.get(&column))) {
+                    (Some((_, value, _)), _) => value,
+                    (None, Some(default)) => {
+                        values.insert(column, default.clone());
+                        Box::new(default.clone())
+                    },
+                    (None, None) => Box::new(None::<TypedString>),
+                });
+            }
+            rows.push(row);
+
+            new_rows.push(return_columns.iter()
+                          .map(|column| (column.clone(), values.get(column).cloned().flatten()))
+                          .collect());
+        }
+
+        let column_names = columns.iter()
+            .map(|column| quote_identifier(column))
+            .collect::<Vec<String>>();
+
+        let types = client.column_types(&format!("select {} from {} limit 0", column_names.join(", "), table.quoted()))?;
+
+        let copy = format!("COPY {} ({}) FROM STDIN (FORMAT binary)", table.quoted(), column_names.join(", "));
+        println!("{}", copy);
+
+        client.copy_in_binary(&copy, &types, &rows)?;
+
+        Ok(new_rows)
+    }
+
+    /// Inserts one row per entry of `set_columns` with multi-row `INSERT`s, setting the given column of each row to
+    /// the given value.
+    fn insert_in_table(&self, rng: &mut impl Rng, client: &mut impl Connection, key_pool: &KeyPool, table: &TableName, set_columns: &[Option<(&str, Option<Value>)>], return_column: Option<&str>) -> Result<Vec<Row>, postgres::Error> {
+        // A row that violates a check that was not understood would abort the transaction, so these rows are
+        // inserted one by one behind a savepoint and generated again if they do
+        if self.tables[table].unparsed_checks() {
+            let mut rows = Vec::new();
+            for set_column in set_columns {
+                for attempt in 1.. {
+                    client.batch_execute("SAVEPOINT pggenerate_row")?;
+                    match self.insert_rows(rng, client, key_pool, table, std::slice::from_ref(set_column), return_column) {
+                        Ok(new_rows) => {
+                            client.batch_execute("RELEASE SAVEPOINT pggenerate_row")?;
+                            rows.extend(new_rows);
+                            break;
+                        },
+                        Err(e) if attempt < 100 && e.code() == Some(&SqlState::CHECK_VIOLATION) => {
+                            println!("{}", e);
+                            client.batch_execute("ROLLBACK TO SAVEPOINT pggenerate_row")?;
+                        },
+                        Err(e) => return Err(e),
+                    }
+                }
+            }
+            return Ok(rows);
+        }
+
+        self.insert_rows(rng, client, key_pool, table, set_columns, return_column)
+    }
+
+    fn insert_rows(&self, rng: &mut impl Rng, client: &mut impl Connection, key_pool: &KeyPool, table: &TableName, set_columns: &[Option<(&str, Option<Value>)>], return_column: Option<&str>) -> Result<Vec<Row>, postgres::Error> {
+        let mut insertion = self.insert_statements(rng, client, key_pool, table, set_columns, return_column)?;
+        let statements = std::mem::take(&mut insertion.statements);
+        let results = client.pipeline(&statements)?;
+
+        Ok(insertion.rows(results))
+    }
+
+    /// Generates the rows for `insert_in_table` and builds the statements to insert them without sending them yet.
+    fn insert_statements(&self, rng: &mut impl Rng, client: &mut impl Connection, key_pool: &KeyPool, table: &TableName, set_columns: &[Option<(&str, Option<Value>)>], return_column: Option<&str>) -> Result<Insertion, postgres::Error> {
+        let table_info = &self.tables[table];
+
+        let set_name = set_columns.iter().flatten().map(|(set_name, _)| *set_name).next();
+        let deferred = self.unresolved_foreign_keys(key_pool, table, set_name);
+        for foreign_key in &deferred {
+            client.execute(&format!("SET CONSTRAINTS {}.{} DEFERRED", quote_identifier(&table.schema), quote_identifier(&foreign_key.name)), &[])?;
+        }
+
+        let mut rows = Vec::<InsertData>::new();
+        for set_column in set_columns {
+            rows.push(self.generate_row(rng, client, key_pool, table, set_column.clone(), &deferred)?.0);
+        }
+
+        let column_names = table_info.column_names.iter()
+            .map(|name| quote_identifier(name))
+            .collect::<Vec<String>>();
+
+        let return_columns = self.return_columns(table, return_column);
+
+        let mut returning = return_columns.iter()
+            .map(|column| quote_identifier(column))
+            .collect::<Vec<String>>();
+        if !deferred.is_empty() {
+            returning.push("ctid::text".to_string());
+        }
+
+        // Postgres accepts at most 65535 parameters per statement, so larger batches are split
+        let mut statements = Vec::<(Vec<String>, Vec<Box<dyn postgres::types::ToSql + Sync>>)>::new();
+        for data in rows {
+            let parameters = data.iter().filter(|(_, idval)| idval.is_some()).count();
+            match statements.last() {
+                Some((_, column_vals)) if column_vals.len() + parameters <= 65535 => (),
+                _ => statements.push((Vec::new(), Vec::new())),
+            }
+            let (values, column_vals) = statements.last_mut().unwrap();
+            let offset = column_vals.len() as i32;
+
+            let column_ids = data.iter()
+                .map(|(_, idval)|
+                     if let Some((id, _, Some(typespecifier))) = idval {format!("${}::{}", offset + id, typespecifier)}
+                     else if let Some((id, _, None)) = idval {format!("${}", offset + id)}
+                     else {"DEFAULT".to_string()})
+                .collect::<Vec<String>>();
+            values.push(format!("({})", column_ids.join(", ")));
+
+            column_vals.extend(data.into_iter().filter_map(|(_, idval)| idval.map(|(_, val, _)| val)));
+        }
+
+        let mut insertion = Insertion {
+            table: table.clone(),
+            statements: Vec::new(),
+            lengths: Vec::new(),
+            return_columns,
+            ctid: !deferred.is_empty(),
+        };
+
+        let overriding = if self.overriding_system_value && table_info.columns.values().any(|column| column.identity_always) {" OVERRIDING SYSTEM VALUE"} else {""};
+        for (values, column_vals) in statements {
+            let insertion_query = if !returning.is_empty() {
+                format!("INSERT INTO {} ({}){} VALUES {} RETURNING {}", table.quoted(), column_names.join(", "), overriding, values.join(", "), returning.join(", "))
+            } else {
+                format!("INSERT INTO {} ({}){} VALUES {}", table.quoted(), column_names.join(", "), overriding, values.join(", "))
+            };
+
+            println!("{}", insertion_query);
+            println!("{:?}", column_vals.iter().map(Box::as_ref).collect::<Vec<&(dyn postgres::types::ToSql + Sync)>>());
+
+            insertion.statements.push((insertion_query, column_vals));
+            insertion.lengths.push(values.len());
+        }
+
+        Ok(insertion)
+    }
+}
 
-        let column_ids = infos.data.iter()
-            .map(|(_, idval)|
-                 if let Some((id, _, Some(typespecifier))) = idval {format!("${}::typespecifier", id)}
-                 else if let Some((id, _, None)) = idval {format!("${}", id)}
-                 else {"DEFAULT".to_string()})
-            .collect::<Vec<String>>();
+fn collect_schemas(client: &mut postgres::Client, params: &Parameters) -> Result<Vec<String>, postgres::Error> {
+    if !params.all_schemas {
+        return Ok(if params.schemas.is_empty() {vec!["public".to_string()]} else {params.schemas.clone()});
+    }
 
-        let column_vals = infos.data.into_iter()
-            .filter_map(|(_, idval)| idval.map(|(_, val, _)| val))
-            .collect::<Vec<Box<(dyn postgres::types::ToSql + Sync)>>>();
+    Ok(client.query("select schema_name from information_schema.schemata where schema_name <> 'information_schema' and schema_name not like 'pg\\_%' order by schema_name", &[])?
+        .into_iter()
+        .map(|row| row.get::<_, String>(0))
+        .collect::<Vec<String>>())
+}
 
-        let column_vals_refs = column_vals.iter()
-            .map(Box::as_ref).collect::<Vec<&(dyn postgres::types::ToSql + Sync)>>();
+/// A column as described by `information_schema.columns`, with the dimensions and element type modifiers of arrays
+#[derive(Debug)]
+struct ColumnRow {
+    pub table: TableName,
+    pub name: String,
+    pub nullable: bool,
+    pub default: Option<String>,
+    pub data_type: String,
+    pub udt_schema: String,
+    pub udt_name: String,
+    pub precision: Option<i32>,
+    pub scale: Option<i32>,
+    pub length: Option<i32>,
+    pub dimensions: i32,
+    pub domain: Option<(String, String)>,
+    pub generated: bool,
+    pub identity: bool,
+    pub identity_always: bool,
+    pub type_name: String,
+}
 
+/// A type defined with `CREATE DOMAIN`, the base type possibly being another domain
+#[derive(Debug)]
+struct Domain {
+    pub schema: String,
+    pub name: String,
+    pub base_schema: String,
+    pub base_name: String,
+    pub base_array: bool,
+    pub base_type: String,
+    pub precision: Option<i32>,
+    pub scale: Option<i32>,
+    pub length: Option<i32>,
+    pub not_null: bool,
+    pub checks: Vec<String>,
+}
 
-        if let Some(return_column) = return_column {
-            let insertion = format!("INSERT INTO \"{}\" ({}) VALUES ({}) RETURNING {}", infos.table, column_names.join(", "), column_ids.join(", "), return_column);
+/// An attribute of a composite type
+#[derive(Debug)]
+struct Attribute {
+    pub type_schema: String,
+    pub type_name: String,
+    pub name: String,
+    pub data_type: String,
+    pub udt_schema: String,
+    pub udt_name: String,
+    pub precision: Option<i32>,
+    pub scale: Option<i32>,
+    pub length: Option<i32>,
+}
 
-            println!("{}", insertion);
-            println!("{:?}", &column_vals_refs[0..]);
+/// The user-defined types that columns can have: enums with their values, range types with their subtypes, domains
+/// and composite types with their attributes
+#[derive(Debug, Default)]
+struct Catalog {
+    pub enums: Vec<(String, String, String)>,
+    pub ranges: Vec<(String, String, String, String)>,
+    pub domains: Vec<Domain>,
+    pub attributes: Vec<Attribute>,
+}
 
-            let res = client.query(
-                &insertion,
-                &column_vals_refs[0..],
-            )?
-                .into_iter()
-                .map(|row| row.get::<_, Value>(0))
-                .next()
-                .unwrap();
+impl Catalog {
+    /// The type a domain is ultimately based on, as needed to cast parameters to it, as the driver can not convert
+    /// values to domains
+    fn domain_base_type(&self, schema: &str, name: &str) -> Option<String> {
+        let mut base_type = None;
+        let mut domain_name = (schema, name);
+        while let Some(domain) = self.domains.iter().find(|domain| (domain.schema.as_str(), domain.name.as_str()) == domain_name) {
+            base_type = Some(domain.base_type.clone());
+            domain_name = (&domain.base_schema, &domain.base_name);
+        }
+        base_type
+    }
 
-            Ok(Some(res))
+    /// The checks of a domain and of all the domains it is based on
+    fn domain_checks(&self, schema: &str, name: &str) -> Vec<Check> {
+        let mut checks = Vec::new();
+        let mut domain_name = (schema, name);
+        while let Some(domain) = self.domains.iter().find(|domain| (domain.schema.as_str(), domain.name.as_str()) == domain_name) {
+            if domain.not_null {
+                checks.push(Check::NotNull);
+            }
+            for definition in &domain.checks {
+                checks.extend(parse_checks(definition, &["VALUE".to_string()]).into_iter().map(|(_, check)| check));
+            }
+            domain_name = (&domain.base_schema, &domain.base_name);
         }
-        else {
-            let insertion = format!("INSERT INTO \"{}\" ({}) VALUES ({})", infos.table, column_names.join(", "), column_ids.join(", "));
+        checks
+    }
 
-            println!("{}", insertion);
-            println!("{:?}", &column_vals_refs[0..]);
+    /// Maps a type of `information_schema.columns` to a `Type`. Domains are resolved to their base type. Only the
+    /// built-in multirange types are known to the driver.
+    fn parse_type(&self, schema: &str, name: &str, precision: Option<i32>, scale: Option<i32>, length: Option<i32>) -> Type {
+        if let Some(domain) = self.domains.iter().find(|domain| domain.schema == schema && domain.name == name) {
+            let (precision, scale, length) = (domain.precision.or(precision), domain.scale.or(scale), domain.length.or(length));
+            return if domain.base_array {
+                Type::Array(Box::new(self.parse_type(&domain.base_schema, &domain.base_name[1..], precision, scale, length)), 1)
+            } else {
+                self.parse_type(&domain.base_schema, &domain.base_name, precision, scale, length)
+            };
+        }
 
-            let res = client.execute(
-                &insertion,
-                &column_vals_refs[0..],
-            )?;
+        let attributes = self.attributes.iter()
+            .filter(|attribute| attribute.type_schema == schema && attribute.type_name == name)
+            .map(|attribute|
+                 if attribute.data_type == "ARRAY" {
+                     let element_type = self.parse_type(&attribute.udt_schema, &attribute.udt_name[1..], attribute.precision, attribute.scale, attribute.length);
+                     (attribute.name.clone(), Type::Array(Box::new(element_type), 1), self.domain_checks(&attribute.udt_schema, &attribute.udt_name[1..]))
+                 } else {
+                     (attribute.name.clone(), self.parse_type(&attribute.udt_schema, &attribute.udt_name, attribute.precision, attribute.scale, attribute.length), self.domain_checks(&attribute.udt_schema, &attribute.udt_name))
+                 })
+            .collect::<Vec<(String, Type, Vec<Check>)>>();
+        if !attributes.is_empty() {
+            return Type::Composite(attributes);
+        }
 
-            Ok(None)
+        let range_name = if schema == "pg_catalog" {name.replace("multirange", "range")} else {name.to_string()};
+        if let Some((_, _, subtype_schema, subtype_name)) = self.ranges.iter().find(|(range_schema, range, _, _)| range_schema == schema && range == &range_name) {
+            let subtype = Box::new(self.parse_type(subtype_schema, subtype_name, None, None, None));
+            return if range_name == name {Type::Range(subtype)} else {Type::MultiRange(subtype)};
         }
+
+        let values = self.enums.iter()
+            .filter(|(typeschema, typename, _)| typeschema == schema && typename == name)
+            .map(|(_, _, valuename)| valuename.clone())
+            .collect::<Vec<String>>();
+
+        if !values.is_empty() {Type::Enum(values)}
+        else if name == "bool" {Type::Bool}
+        else if name == "int2" {Type::Int2}
+        else if name == "int4" {Type::Int4}
+        else if name == "int8" {Type::Int8}
+        else if name == "text" || name == "varchar" || name == "bpchar" {Type::Text(length)}
+        else if name == "uuid" {Type::Uuid}
+        else if name == "bytea" {Type::ByteArray}
+        else if name == "json" {Type::Json}
+        else if name == "jsonb" {Type::Jsonb}
+        else if name == "date" {Type::Date}
+        else if name == "time" {Type::Time}
+        else if name == "timetz" {Type::TimeTz}
+        else if name == "interval" {Type::Interval}
+        else if name == "timestamp" {Type::Timestamp}
+        else if name == "timestamptz" {Type::TimestampTz}
+        // Negative scales are reported in the 11 bits they are stored in
+        else if name == "numeric" {Type::Numeric {precision, scale: scale.map(|scale| if scale >= 1024 {scale - 2048} else {scale})}}
+        else if name == "float4" {Type::Float4}
+        else if name == "float8" {Type::Float8}
+        else if name == "inet" {Type::Inet}
+        else if name == "cidr" {Type::Cidr}
+        else if name == "macaddr" {Type::MacAddr}
+        else if name == "macaddr8" {Type::MacAddr8}
+        else if name == "bit" {Type::Bit(length.unwrap_or(1))}
+        else if name == "varbit" {Type::VarBit(length)}
+        else {panic!("Unexpected type: {}", name)}
     }
 }
 
-fn collect_table_information(client: &mut postgres::Client) -> Result<Database, postgres::Error> {
-    let tables = client.query("select table_name from information_schema.tables where table_schema = 'public' and table_type = 'BASE TABLE' and is_insertable_into = 'YES' and is_typed = 'NO' order by table_name", &[])
+fn collect_table_information(client: &mut postgres::Client, schemas: &[String]) -> Result<Database, postgres::Error> {
+    let tables = client.query("select table_schema, table_name from information_schema.tables where table_schema = any($1) and table_type = 'BASE TABLE' and is_insertable_into = 'YES' and is_typed = 'NO' order by table_schema, table_name", &[&schemas])
         .unwrap()
         .into_iter()
-        .map(|row| row.get::<_, String>(0))
-        .collect::<Vec<String>>();
+        .map(|row|
+             TableName {
+                 schema: row.get::<_, String>(0),
+                 name: row.get::<_, String>(1),
+             })
+        .collect::<Vec<TableName>>();
 
-    let columns = client.query("select table_name, column_name, is_nullable, column_default, data_type, udt_name from information_schema.columns where table_schema = 'public' order by table_name, column_name", &[])
+    let columns = client.query("select table_schema, table_name, column_name, is_nullable, column_default, data_type, udt_schema, udt_name, coalesce(numeric_precision, attribute.element_precision)::int4, coalesce(numeric_scale, attribute.element_scale)::int4, coalesce(character_maximum_length, attribute.element_length)::int4, attribute.attndims::int4, domain_schema::text, domain_name::text, is_generated = 'ALWAYS', is_identity = 'YES', identity_generation = 'ALWAYS', attribute.type_name from information_schema.columns join lateral (select pg_attribute.attndims, format_type(pg_attribute.atttypid, pg_attribute.atttypmod) as type_name, information_schema._pg_numeric_precision(pg_type.typelem, pg_attribute.atttypmod) as element_precision, information_schema._pg_numeric_scale(pg_type.typelem, pg_attribute.atttypmod) as element_scale, information_schema._pg_char_max_length(pg_type.typelem, pg_attribute.atttypmod) as element_length from pg_attribute join pg_type on pg_type.oid = pg_attribute.atttypid where pg_attribute.attrelid = (quote_ident(table_schema) || '.' || quote_ident(table_name))::regclass and pg_attribute.attname = column_name) attribute on true where table_schema = any($1) order by table_schema, table_name, ordinal_position", &[&schemas])
+        .unwrap()
+        .into_iter()
+        .map(|row|
+             ColumnRow {
+                 table: TableName {
+                     schema: row.get::<_, String>(0),
+                     name: row.get::<_, String>(1),
+                 },
+                 name: row.get::<_, String>(2),
+                 nullable: row.get::<_, String>(3) == "YES",
+                 default: row.get::<_, Option<String>>(4),
+                 data_type: row.get::<_, String>(5),
+                 udt_schema: row.get::<_, String>(6),
+                 udt_name: row.get::<_, String>(7),
+                 precision: row.get::<_, Option<i32>>(8),
+                 scale: row.get::<_, Option<i32>>(9),
+                 length: row.get::<_, Option<i32>>(10),
+                 dimensions: row.get::<_, i32>(11),
+                 domain: row.get::<_, Option<String>>(12).zip(row.get::<_, Option<String>>(13)),
+                 generated: row.get::<_, bool>(14),
+                 identity: row.get::<_, bool>(15),
+                 identity_always: row.get::<_, Option<bool>>(16).unwrap_or(false),
+                 type_name: row.get::<_, String>(17),
+             })
+        .collect::<Vec<ColumnRow>>();
+
+    let constraints = client.query("select pg_constraint.contype::text, pg_constraint.conname::text, namespace.nspname::text, class.relname::text, fnamespace.nspname::text, fclass.relname::text, array(select pg_attribute.attname::text from unnest(pg_constraint.conkey) with ordinality as keys(attnum, position) join pg_attribute on pg_attribute.attrelid = pg_constraint.conrelid and pg_attribute.attnum = keys.attnum order by keys.position), array(select pg_attribute.attname::text from unnest(pg_constraint.confkey) with ordinality as keys(attnum, position) join pg_attribute on pg_attribute.attrelid = pg_constraint.confrelid and pg_attribute.attnum = keys.attnum order by keys.position), pg_constraint.condeferrable from pg_constraint join pg_class class on class.oid = pg_constraint.conrelid join pg_namespace namespace on namespace.oid = class.relnamespace left join pg_class fclass on fclass.oid = pg_constraint.confrelid left join pg_namespace fnamespace on fnamespace.oid = fclass.relnamespace where pg_constraint.contype in ('p', 'f') and namespace.nspname = any($1) order by namespace.nspname, class.relname, pg_constraint.conname;", &[&schemas])
         .unwrap()
         .into_iter()
         .map(|row|
              (row.get::<_, String>(0),
               row.get::<_, String>(1),
+              TableName {
+                  schema: row.get::<_, String>(2),
+                  name: row.get::<_, String>(3),
+              },
+              row.get::<_, Option<String>>(4).zip(row.get::<_, Option<String>>(5))
+                  .map(|(schema, name)| TableName {schema, name}),
+              row.get::<_, Vec<String>>(6),
+              row.get::<_, Vec<String>>(7),
+              row.get::<_, bool>(8)))
+        .collect::<Vec<(String, String, TableName, Option<TableName>, Vec<String>, Vec<String>, bool)>>();
+
+    // Unique constraints and primary keys have unique indexes as well. Indexes on expressions and the
+    // columns an index only includes are left out.
+    let unique_keys = client.query("select namespace.nspname::text, class.relname::text, array(select pg_attribute.attname::text from unnest(pg_index.indkey::int2[]) with ordinality as keys(attnum, position) join pg_attribute on pg_attribute.attrelid = pg_index.indrelid and pg_attribute.attnum = keys.attnum where keys.position <= pg_index.indnkeyatts order by keys.position) from pg_index join pg_class class on class.oid = pg_index.indrelid join pg_namespace namespace on namespace.oid = class.relnamespace where pg_index.indisunique and pg_index.indexprs is null and namespace.nspname = any($1) order by namespace.nspname, class.relname, pg_index.indexrelid", &[&schemas])
+        .unwrap()
+        .into_iter()
+        .map(|row|
+             (TableName {
+                 schema: row.get::<_, String>(0),
+                 name: row.get::<_, String>(1),
+             },
+              row.get::<_, Vec<String>>(2)))
+        .collect::<Vec<(TableName, Vec<String>)>>();
+
+    let checks = client.query("select namespace.nspname::text, class.relname::text, pg_get_constraintdef(pg_constraint.oid), array(select pg_attribute.attname::text from unnest(pg_constraint.conkey) as keys(attnum) join pg_attribute on pg_attribute.attrelid = pg_constraint.conrelid and pg_attribute.attnum = keys.attnum) from pg_constraint join pg_class class on class.oid = pg_constraint.conrelid join pg_namespace namespace on namespace.oid = class.relnamespace where pg_constraint.contype = 'c' and namespace.nspname = any($1) order by namespace.nspname, class.relname, pg_constraint.conname", &[&schemas])
+        .unwrap()
+        .into_iter()
+        .map(|row|
+             (TableName {
+                 schema: row.get::<_, String>(0),
+                 name: row.get::<_, String>(1),
+             },
               row.get::<_, String>(2),
-              row.get::<_, Option<String>>(3),
-              row.get::<_, String>(4),
-              row.get::<_, String>(5)))
-        .collect::<Vec<(String, String, String, Option<String>, String, String)>>();
+              row.get::<_, Vec<String>>(3)))
+        .collect::<Vec<(TableName, String, Vec<String>)>>();
 
-    let constraints = client.query("select constraint_name, table_constraints.constraint_type, key_column_usage.table_name, key_column_usage.column_name, constraint_column_usage.table_name, constraint_column_usage.column_name from information_schema.table_constraints join information_schema.key_column_usage using(constraint_name) join information_schema.constraint_column_usage using(constraint_name) where table_constraints.constraint_schema = 'public' and table_constraints.table_schema = 'public' and key_column_usage.constraint_schema = 'public' and key_column_usage.table_schema = 'public' and constraint_column_usage.constraint_schema = 'public' and constraint_column_usage.table_schema = 'public' order by constraint_name;", &[])
+    let enums = client.query("select pg_namespace.nspname, pg_type.typname, pg_enum.enumlabel from pg_type join pg_namespace on pg_namespace.oid = pg_type.typnamespace join pg_enum on pg_enum.enumtypid = pg_type.oid where pg_type.typtype = 'e' and pg_type.typcategory = 'E' order by pg_namespace.nspname, pg_type.typname, pg_enum.enumsortorder;
+", &[])
         .unwrap()
         .into_iter()
         .map(|row|
              (row.get::<_, String>(0),
               row.get::<_, String>(1),
-              row.get::<_, String>(2),
-              row.get::<_, String>(3),
-              row.get::<_, String>(4),
-              row.get::<_, String>(5)))
-        .collect::<Vec<(String, String, String, String, String, String)>>();
+              row.get::<_, String>(2)))
+        .collect::<Vec<(String, String, String)>>();
 
-    let type_values = client.query("select pg_type.typname, pg_enum.enumlabel from pg_type join pg_enum on pg_enum.enumtypid = pg_type.oid where pg_type.typtype = 'e' and pg_type.typcategory = 'E' order by pg_type.typname;
-", &[])
+    let ranges = client.query("select range_namespace.nspname::text, range_type.typname::text, subtype_namespace.nspname::text, subtype.typname::text from pg_range join pg_type range_type on range_type.oid = pg_range.rngtypid join pg_namespace range_namespace on range_namespace.oid = range_type.typnamespace join pg_type subtype on subtype.oid = pg_range.rngsubtype join pg_namespace subtype_namespace on subtype_namespace.oid = subtype.typnamespace", &[])
         .unwrap()
         .into_iter()
         .map(|row|
              (row.get::<_, String>(0),
-              row.get::<_, String>(1)))
-        .collect::<Vec<(String, String)>>();
+              row.get::<_, String>(1),
+              row.get::<_, String>(2),
+              row.get::<_, String>(3)))
+        .collect::<Vec<(String, String, String, String)>>();
 
-    let mut db = Database::default();
-    for table in tables {
-        let table_name = table;
+    let domains = client.query("select namespace.nspname::text, domain.typname::text, base_namespace.nspname::text, base.typname::text, base.typcategory = 'A', format_type(domain.typbasetype, domain.typtypmod), information_schema._pg_numeric_precision(domain.typbasetype, domain.typtypmod), information_schema._pg_numeric_scale(domain.typbasetype, domain.typtypmod), information_schema._pg_char_max_length(domain.typbasetype, domain.typtypmod), domain.typnotnull, array(select pg_get_constraintdef(pg_constraint.oid) from pg_constraint where pg_constraint.contypid = domain.oid and pg_constraint.contype = 'c' order by pg_constraint.conname) from pg_type domain join pg_namespace namespace on namespace.oid = domain.typnamespace join pg_type base on base.oid = domain.typbasetype join pg_namespace base_namespace on base_namespace.oid = base.typnamespace where domain.typtype = 'd'", &[])
+        .unwrap()
+        .into_iter()
+        .map(|row|
+             Domain {
+                 schema: row.get::<_, String>(0),
+                 name: row.get::<_, String>(1),
+                 base_schema: row.get::<_, String>(2),
+                 base_name: row.get::<_, String>(3),
+                 base_array: row.get::<_, bool>(4),
+                 base_type: row.get::<_, String>(5),
+                 precision: row.get::<_, Option<i32>>(6),
+                 scale: row.get::<_, Option<i32>>(7),
+                 length: row.get::<_, Option<i32>>(8),
+                 not_null: row.get::<_, bool>(9),
+                 checks: row.get::<_, Vec<String>>(10),
+             })
+        .collect::<Vec<Domain>>();
+
+    let attributes = client.query("select udt_schema::text, udt_name::text, attribute_name::text, data_type::text, attribute_udt_schema::text, attribute_udt_name::text, coalesce(numeric_precision, attribute.element_precision)::int4, coalesce(numeric_scale, attribute.element_scale)::int4, coalesce(character_maximum_length, attribute.element_length)::int4 from information_schema.attributes join lateral (select information_schema._pg_numeric_precision(pg_type.typelem, pg_attribute.atttypmod) as element_precision, information_schema._pg_numeric_scale(pg_type.typelem, pg_attribute.atttypmod) as element_scale, information_schema._pg_char_max_length(pg_type.typelem, pg_attribute.atttypmod) as element_length from pg_attribute join pg_type on pg_type.oid = pg_attribute.atttypid where pg_attribute.attrelid = (quote_ident(udt_schema) || '.' || quote_ident(udt_name))::regclass and pg_attribute.attname = attribute_name) attribute on true order by udt_schema, udt_name, ordinal_position", &[])
+        .unwrap()
+        .into_iter()
+        .map(|row|
+             Attribute {
+                 type_schema: row.get::<_, String>(0),
+                 type_name: row.get::<_, String>(1),
+                 name: row.get::<_, String>(2),
+                 data_type: row.get::<_, String>(3),
+                 udt_schema: row.get::<_, String>(4),
+                 udt_name: row.get::<_, String>(5),
+                 precision: row.get::<_, Option<i32>>(6),
+                 scale: row.get::<_, Option<i32>>(7),
+                 length: row.get::<_, Option<i32>>(8),
+             })
+        .collect::<Vec<Attribute>>();
+
+    let catalog = Catalog {enums, ranges, domains, attributes};
 
-        db.tables.insert(table_name.clone(), Table::default());
+    let mut db = Database::default();
+    for table_name in tables {
+        db.tables.insert(table_name.clone(), Table {
+            name: table_name.clone(),
+            columns: BTreeMap::new(),
+            column_names: Vec::new(),
+            foreign_keys: Vec::new(),
+            referenced_keys: Vec::new(),
+            unique_keys: Vec::new(),
+        });
         db.table_names.push(table_name);
     }
 
     for column in columns {
-        let table_name = column.0;
-        let column_name = column.1;
-        let is_nullable = column.2 == "YES";
-        let has_default = column.3.is_some();
-        let value_type = if column.4 == "USER-DEFINED" {
-            let values = type_values.iter()
-                .filter(|(typename, _)| typename == &column.5)
-                .map(|(_, valuename)| valuename.clone())
-                .collect::<Vec<String>>();
-            Type::Enum(values)
-        } else if column.4 == "ARRAY" {
-            if column.5 == "_text" {
-                Type::Array(Box::new(Type::Text))
-            } else {panic!("Unexpected ARRAY type: {}", column.5)}
+        let table_name = column.table;
+        let column_name = column.name;
+        // Identity columns have no default in `information_schema`, but their next values can be calculated alike
+        let default = if column.identity {
+            Some(format!("nextval(pg_get_serial_sequence('{}', '{}'))", table_name.quoted().replace('\'', "''"), column_name.replace('\'', "''")))
+        } else {
+            column.default
+        };
+        let (value_type, checks, type_specifier) = if column.data_type == "ARRAY" && column.domain.is_none() {
+            let element_type = catalog.parse_type(&column.udt_schema, &column.udt_name[1..], column.precision, column.scale, column.length);
+            let type_specifier = catalog.domain_base_type(&column.udt_schema, &column.udt_name[1..]).map(|base_type| format!("{}[]", base_type));
+            (Type::Array(Box::new(element_type), column.dimensions.max(1)), catalog.domain_checks(&column.udt_schema, &column.udt_name[1..]), type_specifier)
+        } else if let Some((domain_schema, domain_name)) = &column.domain {
+            (catalog.parse_type(domain_schema, domain_name, column.precision, column.scale, column.length), catalog.domain_checks(domain_schema, domain_name), catalog.domain_base_type(domain_schema, domain_name))
         } else {
-            if column.5 == "bool" {Type::Bool}
-            else if column.5 == "int4" {Type::Int4}
-            else if column.5 == "int8" {Type::Int8}
-            else if column.5 == "text" {Type::Text}
-            else if column.5 == "varchar" {Type::Text}
-            else if column.5 == "bytea" {Type::ByteArray}
-            else if column.5 == "jsonb" {Type::JSON}
-            else if column.5 == "timestamp" {Type::Timestamp}
-            else if column.5 == "timestamptz" {Type::Timestamp}
-            else {panic!("Unexpected type: {}", column.5)}
+            (catalog.parse_type(&column.udt_schema, &column.udt_name, column.precision, column.scale, column.length), Vec::new(), None)
         };
+        let is_nullable = column.nullable && (column.domain.is_none() || !checks.contains(&Check::NotNull));
 
         let primary_key = constraints.iter()
-            .filter(|(_, _, table, column, _, _)| &table_name == table && &column_name == column)
-            .any(|(_, constraint_type, _, _, _, _)| constraint_type == "PRIMARY KEY");
-
-        let mut foreign_keys = constraints.iter()
-            .filter(|(_, _, table, column, _, _)| &table_name == table && &column_name == column)
-            .filter(|(_, constraint_type, _, _, _, _)| constraint_type == "FOREIGN KEY")
-            .map(|(_, _, _, _, table, column)| (table.clone(), column.clone()))
-            .collect::<Vec<(String, String)>>();
-
-        if foreign_keys.len() > 1 {
-            panic!("More than one foreign key information found for one column:\n{:#?}", constraints.iter()
-                   .filter(|(_, _, table, column, _, _)| &table_name == table && &column_name == column)
-                   .filter(|(_, constraint_type, _, _, _, _)| constraint_type == "FOREIGN KEY"));
-        }
+            .filter(|(constraint_type, _, table, _, _, _, _)| constraint_type == "p" && &table_name == table)
+            .any(|(_, _, _, _, columns, _, _)| columns.contains(&column_name));
+        let unique = unique_keys.iter()
+            .any(|(table, columns)| &table_name == table && columns.contains(&column_name));
 
         let column = Column {
             name: column_name.clone(),
             value_type,
             value_nullable: is_nullable,
-            value_default: has_default,
+            value_default: default,
             primary_key,
-            foreign_key: foreign_keys.pop()
+            unique,
+            generated: column.generated,
+            identity: column.identity,
+            identity_always: column.identity_always,
+            type_name: column.type_name,
+            json_schema: None,
+            string_length: None,
+            checks,
+            type_specifier,
         };
 
         if let Some(table) = db.tables.get_mut(&table_name) {
-            (*table).columns.insert(column_name.clone(), column);
-            (*table).column_names.push(column_name);
+            table.columns.insert(column_name.clone(), column);
+            table.column_names.push(column_name);
         } else {
             println!("Table {} not found for column {}", table_name, column_name);
         }
     }
 
+    for (table_name, definition, columns) in checks {
+        if let Some(table) = db.tables.get_mut(&table_name) {
+            for (column, check) in parse_checks(&definition, &columns) {
+                let column = table.columns.get_mut(&column).unwrap();
+                if check == Check::NotNull {
+                    column.value_nullable = false;
+                }
+                column.checks.push(check);
+            }
+        }
+    }
+
+    for (_, name, table_name, ftable_name, columns, fcolumns, deferrable) in constraints.into_iter().filter(|(constraint_type, _, _, _, _, _, _)| constraint_type == "f") {
+        let ftable_name = ftable_name.unwrap();
+        let mut key_type = false;
+        if let Some(table) = db.tables.get_mut(&table_name) {
+            let nullable = columns.iter().all(|column| table.columns[column].value_nullable);
+            key_type = columns.iter().all(|column| is_key_type(&table.columns[column].value_type));
+            table.foreign_keys.push(ForeignKey {
+                name,
+                table: ftable_name.clone(),
+                columns: columns.into_iter().zip(fcolumns.iter().cloned()).collect(),
+                nullable,
+                deferrable,
+                cyclic: false,
+            });
+        }
+        // Keys of other types can't be returned, the referencing tables are left out
+        if let Some(ftable) = db.tables.get_mut(&ftable_name).filter(|_| key_type) {
+            if !ftable.referenced_keys.contains(&fcolumns) {
+                ftable.referenced_keys.push(fcolumns);
+            }
+        }
+    }
+
+    for (table_name, columns) in unique_keys {
+        if let Some(table) = db.tables.get_mut(&table_name) {
+            if !table.unique_keys.contains(&columns) {
+                table.unique_keys.push(columns);
+            }
+        }
+    }
+
+    for table in db.tables.values_mut() {
+        table.mark_unsatisfiable_checks();
+    }
+
+    db.mark_cyclic_foreign_keys();
+    db.table_names = db.dependency_order();
+
     Ok(db)
 }
 
 struct Parameters {
+    pub schemas: Vec<String>,
+    pub all_schemas: bool,
     pub onlys: Vec<String>,
     pub skips: BTreeSet<String>,
     pub require_afters: BTreeMap<String, (String, String, String)>,
     pub require_befores: BTreeMap<String, (String, String, String)>,
+    pub max_rows: Option<u64>,
+    pub table_rows: BTreeMap<String, u64>,
+    pub weights: BTreeMap<String, u32>,
+    pub max_transactions: Option<u64>,
+    pub max_duration: Option<Duration>,
+    pub seed: Option<u64>,
+    pub key_pool_size: usize,
+    pub key_refresh: u64,
+    pub max_depth: Option<u32>,
+    pub batch_size: usize,
+    pub rows_per_transaction: Option<usize>,
+    pub copy: bool,
+    pub jobs: usize,
+    pub pipeline: usize,
+    pub non_finite: bool,
+    pub time_range: (i64, i64),
+    pub uuid_v7: bool,
+    pub array_length: i32,
+    pub array_nulls: bool,
+    pub json_depth: u32,
+    pub json_keys: usize,
+    pub json_schemas: Vec<(String, String, serde_json::Value)>,
+    pub string_lengths: Vec<(String, String, usize, usize)>,
+    pub overriding_system_value: bool,
+}
+
+fn parse_duration(duration: &str) -> Duration {
+    let (number, unit) = duration.split_at(duration.find(|c: char| !c.is_ascii_digit()).unwrap_or(duration.len()));
+    let number = number.parse::<u64>().unwrap_or_else(|_| panic!("Could not parse duration '{}'", duration));
+
+    match unit {
+        "ms" => Duration::from_millis(number),
+        "" | "s" => Duration::from_secs(number),
+        "m" => Duration::from_secs(number * 60),
+        "h" => Duration::from_secs(number * 60 * 60),
+        _ => panic!("Unknown unit '{}' in duration '{}', expecting ms, s, m or h", unit, duration),
+    }
+}
+
+/// Parses `now` or a date like `2015-01-01` into microseconds since the Unix epoch
+fn parse_time(time: &str) -> i64 {
+    let timestamp = if time == "now" {
+        OffsetDateTime::now_utc()
+    } else {
+        let parts = time.split('-')
+            .map(|part| part.parse::<i32>().ok())
+            .collect::<Option<Vec<i32>>>()
+            .filter(|parts| parts.len() == 3)
+            .unwrap_or_else(|| panic!("Could not parse date '{}', expecting e.g. 2015-01-01 or now", time));
+        Month::try_from(parts[1] as u8).ok()
+            .and_then(|month| Date::from_calendar_date(parts[0], month, parts[2] as u8).ok())
+            .unwrap_or_else(|| panic!("Invalid date '{}'", time))
+            .midnight()
+            .assume_utc()
+    };
+
+    (timestamp.unix_timestamp_nanos() / 1000) as i64
 }
 
 fn parse_arguments() -> Parameters {
+    let mut schemas = Vec::<String>::new();
+    let mut all_schemas = false;
     let mut onlys = Vec::<String>::new();
     let mut skips = BTreeSet::<String>::new();
     let mut require_afters = BTreeMap::<String, (String, String, String)>::new();
     let mut require_befores = BTreeMap::<String, (String, String, String)>::new();
+    let mut max_rows = None;
+    let mut table_rows = BTreeMap::<String, u64>::new();
+    let mut weights = BTreeMap::<String, u32>::new();
+    let mut max_transactions = None;
+    let mut max_duration = None;
+    let mut seed = None;
+    let mut key_pool_size = 100000;
+    let mut key_refresh = 10000;
+    let mut max_depth = None;
+    let mut batch_size = 1;
+    let mut rows_per_transaction = None;
+    let mut copy = false;
+    let mut non_finite = false;
+    let mut uuid_v7 = false;
+    let mut array_length = 5;
+    let mut array_nulls = false;
+    let mut overriding_system_value = false;
+    let mut json_depth = 3;
+    let mut json_keys = 5;
+    let mut json_schemas = Vec::<(String, String, serde_json::Value)>::new();
+    let mut string_lengths = Vec::<(String, String, usize, usize)>::new();
+    let mut time_range = (parse_time("now") - 365 * 86_400_000_000, parse_time("now"));
+    let mut jobs = 1;
+    let mut pipeline = 1;
 
     for argument in std::env::args().skip(2) {
         if let Some(require_after_arguments) = argument.strip_prefix("--require-after=") {
-            if let [table, column, atable, acolumn] = &require_after_arguments.split(",").collect::<Vec<&str>>()[..]  {
+            if let [table, column, atable, acolumn] = &require_after_arguments.split(',').collect::<Vec<&str>>()[..]  {
                 require_afters.insert(table.to_string(), (column.to_string(), atable.to_string(), acolumn.to_string()));
             } else {
                 panic!("Wrong arguments to --require-after=: Expecting 'table,column,aftertable,aftercolumn', got '{}'", require_after_arguments);
             }
         }
         else if let Some(require_before_arguments) = argument.strip_prefix("--require-before=") {
-            if let [table, column, btable, bcolumn] = &require_before_arguments.split(",").collect::<Vec<&str>>()[..]  {
+            if let [table, column, btable, bcolumn] = &require_before_arguments.split(',').collect::<Vec<&str>>()[..]  {
                 require_befores.insert(table.to_string(), (column.to_string(), btable.to_string(), bcolumn.to_string()));
             } else {
                 panic!("Wrong arguments to --require-before=: Expecting 'table,column,beforetable,beforecolumn', got '{}'", require_before_arguments);
             }
         }
+        else if let Some(schema_argument) = argument.strip_prefix("--schema=") {
+            schemas.push(schema_argument.to_string());
+        }
+        else if argument == "--all-schemas" {
+            all_schemas = true;
+        }
         else if let Some(only_argument) = argument.strip_prefix("--only=") {
             onlys.push(only_argument.to_string());
         }
         else if let Some(skip_argument) = argument.strip_prefix("--skip=") {
             skips.insert(skip_argument.to_string());
+        }
+        else if let Some(rows_argument) = argument.strip_prefix("--rows=") {
+            if let Some((table, rows)) = rows_argument.rsplit_once(':') {
+                table_rows.insert(table.to_string(), rows.parse::<u64>().unwrap_or_else(|_| panic!("Wrong argument to --rows=: Expecting 'table:n', got '{}'", rows_argument)));
+            } else {
+                max_rows = Some(rows_argument.parse::<u64>().unwrap_or_else(|_| panic!("Wrong argument to --rows=: Expecting a number or 'table:n', got '{}'", rows_argument)));
+            }
+        }
+        else if let Some(weight_argument) = argument.strip_prefix("--weight=") {
+            if let Some((table, weight)) = weight_argument.rsplit_once(':') {
+                weights.insert(table.to_string(), weight.parse::<u32>().unwrap_or_else(|_| panic!("Wrong argument to --weight=: Expecting 'table:n', got '{}'", weight_argument)));
+            } else {
+                panic!("Wrong argument to --weight=: Expecting 'table:n', got '{}'", weight_argument);
+            }
+        }
+        else if let Some(transactions_argument) = argument.strip_prefix("--transactions=") {
+            max_transactions = Some(transactions_argument.parse::<u64>().unwrap_or_else(|_| panic!("Wrong argument to --transactions=: Expecting a number, got '{}'", transactions_argument)));
+        }
+        else if let Some(duration_argument) = argument.strip_prefix("--duration=") {
+            max_duration = Some(parse_duration(duration_argument));
+        }
+        else if let Some(seed_argument) = argument.strip_prefix("--seed=") {
+            seed = Some(seed_argument.parse::<u64>().unwrap_or_else(|_| panic!("Wrong argument to --seed=: Expecting a number, got '{}'", seed_argument)));
+        }
+        else if let Some(key_pool_size_argument) = argument.strip_prefix("--key-pool-size=") {
+            key_pool_size = key_pool_size_argument.parse::<usize>().unwrap_or_else(|_| panic!("Wrong argument to --key-pool-size=: Expecting a number, got '{}'", key_pool_size_argument));
+        }
+        else if let Some(key_refresh_argument) = argument.strip_prefix("--key-refresh=") {
+            key_refresh = key_refresh_argument.parse::<u64>().ok().filter(|key_refresh| *key_refresh > 0).unwrap_or_else(|| panic!("Wrong argument to --key-refresh=: Expecting a positive number, got '{}'", key_refresh_argument));
+        }
+        else if let Some(batch_size_argument) = argument.strip_prefix("--batch-size=") {
+            batch_size = batch_size_argument.parse::<usize>().ok().filter(|batch_size| *batch_size > 0).unwrap_or_else(|| panic!("Wrong argument to --batch-size=: Expecting a positive number, got '{}'", batch_size_argument));
+        }
+        else if let Some(rows_per_transaction_argument) = argument.strip_prefix("--rows-per-transaction=") {
+            rows_per_transaction = Some(rows_per_transaction_argument.parse::<usize>().ok().filter(|rows_per_transaction| *rows_per_transaction > 0).unwrap_or_else(|| panic!("Wrong argument to --rows-per-transaction=: Expecting a positive number, got '{}'", rows_per_transaction_argument)));
+        }
+        else if let Some(jobs_argument) = argument.strip_prefix("--jobs=") {
+            jobs = jobs_argument.parse::<usize>().ok().filter(|jobs| *jobs > 0).unwrap_or_else(|| panic!("Wrong argument to --jobs=: Expecting a positive number, got '{}'", jobs_argument));
+        }
+        else if let Some(pipeline_argument) = argument.strip_prefix("--pipeline=") {
+            pipeline = pipeline_argument.parse::<usize>().ok().filter(|pipeline| *pipeline > 0).unwrap_or_else(|| panic!("Wrong argument to --pipeline=: Expecting a positive number, got '{}'", pipeline_argument));
+        }
+        else if argument == "--copy" {
+            copy = true;
+        }
+        else if argument == "--non-finite" {
+            non_finite = true;
+        }
+        else if let Some(array_length_argument) = argument.strip_prefix("--array-length=") {
+            array_length = array_length_argument.parse::<i32>().ok().filter(|array_length| *array_length > 0).unwrap_or_else(|| panic!("Wrong argument to --array-length=: Expecting a positive number, got '{}'", array_length_argument));
+        }
+        else if argument == "--array-nulls" {
+            array_nulls = true;
+        }
+        else if argument == "--overriding-system-value" {
+            overriding_system_value = true;
+        }
+        else if let Some(json_depth_argument) = argument.strip_prefix("--json-depth=") {
+            json_depth = json_depth_argument.parse::<u32>().unwrap_or_else(|_| panic!("Wrong argument to --json-depth=: Expecting a number, got '{}'", json_depth_argument));
+        }
+        else if let Some(json_keys_argument) = argument.strip_prefix("--json-keys=") {
+            json_keys = json_keys_argument.parse::<usize>().unwrap_or_else(|_| panic!("Wrong argument to --json-keys=: Expecting a number, got '{}'", json_keys_argument));
+        }
+        else if let Some(json_schema_arguments) = argument.strip_prefix("--json-schema=") {
+            if let [table, column, file] = &json_schema_arguments.splitn(3, ',').collect::<Vec<&str>>()[..] {
+                let schema = std::fs::read_to_string(file).unwrap_or_else(|e| panic!("Could not read JSON schema '{}': {}", file, e));
+                let schema = serde_json::from_str(&schema).unwrap_or_else(|e| panic!("Could not parse JSON schema '{}': {}", file, e));
+                json_schemas.push((table.to_string(), column.to_string(), schema));
+            } else {
+                panic!("Wrong arguments to --json-schema=: Expecting 'table,column,file', got '{}'", json_schema_arguments);
+            }
+        }
+        else if let Some(string_length_arguments) = argument.strip_prefix("--string-length=") {
+            if let [table, column, min, max] = &string_length_arguments.split(',').collect::<Vec<&str>>()[..] {
+                match (min.parse::<usize>(), max.parse::<usize>()) {
+                    (Ok(min), Ok(max)) if min <= max => string_lengths.push((table.to_string(), column.to_string(), min, max)),
+                    _ => panic!("Wrong arguments to --string-length=: Expecting numbers with min <= max, got '{}'", string_length_arguments),
+                }
+            } else {
+                panic!("Wrong arguments to --string-length=: Expecting 'table,column,min,max', got '{}'", string_length_arguments);
+            }
+        }
+        else if let Some(uuid_version_argument) = argument.strip_prefix("--uuid-version=") {
+            uuid_v7 = match uuid_version_argument {
+                "4" => false,
+                "7" => true,
+                _ => panic!("Wrong argument to --uuid-version=: Expecting 4 or 7, got '{}'", uuid_version_argument),
+            };
+        }
+        else if let Some(time_range_argument) = argument.strip_prefix("--time-range=") {
+            let (start, end) = time_range_argument.split_once(',').unwrap_or_else(|| panic!("Wrong argument to --time-range=: Expecting start,end, got '{}'", time_range_argument));
+            time_range = (parse_time(start), parse_time(end));
+            if time_range.0 > time_range.1 {
+                panic!("Wrong argument to --time-range=: Start {} is after end {}", start, end);
+            }
+        }
+        else if let Some(max_depth_argument) = argument.strip_prefix("--max-depth=") {
+            max_depth = Some(max_depth_argument.parse::<u32>().ok().filter(|max_depth| *max_depth > 0).unwrap_or_else(|| panic!("Wrong argument to --max-depth=: Expecting a positive number, got '{}'", max_depth_argument)));
         } else {
             if argument != "--help" {
                 println!("Unknow parameter {}", argument);
             }
-            panic!("Possible parameters are \n  --skip=table\n  --only=table\n  --require-after=table,column,atable,acolumn\n  --require-before=table,column,btable,bcolumn");
+            panic!("Possible parameters are \n  --schema=schema\n  --all-schemas\n  --skip=table\n  --only=table\n  --require-after=table,column,atable,acolumn\n  --require-before=table,column,btable,bcolumn\n  --rows=n\n  --rows=table:n\n  --weight=table:n\n  --transactions=n\n  --duration=time\n  --seed=n\n  --key-pool-size=n\n  --key-refresh=n\n  --max-depth=n\n  --batch-size=n\n  --rows-per-transaction=n\n  --copy\n  --jobs=n\n  --pipeline=n\n  --non-finite\n  --time-range=start,end\n  --uuid-version=n\n  --array-length=n\n  --array-nulls\n  --json-depth=n\n  --json-keys=n\n  --json-schema=table,column,file\n  --string-length=table,column,min,max\n  --overriding-system-value");
         }
     }
 
-    if skips.len() > 0 && onlys.len() > 0 {
+    if !skips.is_empty() && !onlys.is_empty() {
         panic!("Parameters '--only=' and '--skip' can not be combined.");
     }
 
+    if !schemas.is_empty() && all_schemas {
+        panic!("Parameters '--schema=' and '--all-schemas' can not be combined.");
+    }
+
     Parameters {
+        schemas,
+        all_schemas,
         onlys,
         skips,
         require_afters,
         require_befores,
+        max_rows,
+        table_rows,
+        weights,
+        max_transactions,
+        max_duration,
+        seed,
+        key_pool_size,
+        key_refresh,
+        max_depth,
+        batch_size,
+        rows_per_transaction,
+        copy,
+        jobs,
+        pipeline,
+        non_finite,
+        time_range,
+        uuid_v7,
+        array_length,
+        array_nulls,
+        json_depth,
+        json_keys,
+        json_schemas,
+        string_lengths,
+        overriding_system_value,
     }
 }
 
-fn run() -> Result<(), postgres::Error> {
-    let mut client = Client::connect(&std::env::args().skip(1).next().unwrap(), NoTls)?;
+/// Counts of all workers. Rows and transactions that a worker is about to create are claimed first, so that the
+/// workers together don't overshoot the limits.
+#[derive(Debug, Default)]
+struct Progress {
+    pub inserted: BTreeMap<TableName, u64>,
+    pub rows: u64,
+    pub transactions: u64,
+    pub claimed: BTreeMap<TableName, u64>,
+    pub claimed_rows: u64,
+    pub claimed_transactions: u64,
+}
 
-    let db = collect_table_information(&mut client)?;
+impl Progress {
+    fn count(&self, table: &TableName) -> u64 {
+        self.inserted.get(table).copied().unwrap_or(0) + self.claimed.get(table).copied().unwrap_or(0)
+    }
 
-    let mut rng = rand::thread_rng();
-    let mut insertions = 0;
+    fn running(&self, params: &Parameters, started: Instant, table_rows: &BTreeMap<TableName, u64>) -> bool {
+        params.max_rows.is_none_or(|max_rows| self.rows + self.claimed_rows < max_rows)
+            && params.max_transactions.is_none_or(|max_transactions| self.transactions + self.claimed_transactions < max_transactions)
+            && params.max_duration.is_none_or(|max_duration| started.elapsed() < max_duration)
+            && (table_rows.is_empty() || table_rows.iter().any(|(table, rows)| self.count(table) < *rows))
+    }
 
-    let params = parse_arguments();
+    fn release(&mut self, claimed: &BTreeMap<TableName, u64>) {
+        for (table, rows) in claimed {
+            *self.claimed.get_mut(table).unwrap() -= rows;
+            self.claimed_rows -= rows;
+        }
+        self.claimed_transactions -= 1;
+    }
 
-    loop {
-        let random_table: &String = if params.onlys.len() > 0 {
-            params.onlys.choose(&mut rng).unwrap()
-        } else {
-            db.table_names.choose(&mut rng).unwrap()
-        };
+    fn commit(&mut self, new_rows: &[(TableName, Row)]) {
+        for (table, _) in new_rows {
+            *self.inserted.entry(table.clone()).or_insert(0) += 1;
+        }
+        self.rows += new_rows.len() as u64;
+        self.transactions += 1;
+    }
+}
 
-        if params.skips.contains(random_table) {continue}
+/// What the workers share. Each worker runs on its own connection with its own random number generator.
+struct Worker<'a> {
+    pub db: &'a Database,
+    pub params: &'a Parameters,
+    pub tables: &'a [TableName],
+    pub require_afters: &'a BTreeMap<TableName, (String, TableName, String)>,
+    pub require_befores: &'a BTreeMap<TableName, (String, TableName, String)>,
+    pub table_rows: &'a BTreeMap<TableName, u64>,
+    pub weights: &'a BTreeMap<TableName, u32>,
+    pub stop: &'a AtomicBool,
+    pub progress: &'a Mutex<Progress>,
+    pub started: Instant,
+    pub rows_per_transaction: usize,
+}
 
-        println!("Creating new row for table: {}", random_table);
+impl Worker<'_> {
+    fn insert_batch(&self, rng: &mut impl Rng, client: &mut impl Connection, key_pool: &mut KeyPool, table: &TableName, batch_size: usize) -> Result<Vec<(TableName, Row)>, postgres::Error> {
+        let db = self.db;
+        if let Some((rcolumn, atable, acolumn)) = self.require_afters.get(table) {
+            let mut rows = db.insert_with_parents(rng, client, key_pool, table, &vec![None; batch_size], Some(rcolumn), &mut BTreeSet::new())?;
+            let set_columns = rows[rows.len() - batch_size..].iter()
+                .map(|(_, row)| Some((acolumn.as_str(), row[rcolumn].clone())))
+                .collect::<Vec<Option<(&str, Option<Value>)>>>();
+            rows.extend(db.insert_with_parents(rng, client, key_pool, atable, &set_columns, None, &mut BTreeSet::new())?);
+
+            Ok(rows)
+        } else if let Some((rcolumn, btable, bcolumn)) = self.require_befores.get(table) {
+            let mut rows = db.insert_with_parents(rng, client, key_pool, btable, &vec![None; batch_size], Some(bcolumn), &mut BTreeSet::new())?;
+            let set_columns = rows[rows.len() - batch_size..].iter()
+                .map(|(_, row)| Some((rcolumn.as_str(), row[bcolumn].clone())))
+                .collect::<Vec<Option<(&str, Option<Value>)>>>();
+            rows.extend(db.insert_with_parents(rng, client, key_pool, table, &set_columns, None, &mut BTreeSet::new())?);
+
+            Ok(rows)
+        } else {
+            db.insert_with_parents(rng, client, key_pool, table, &vec![None; batch_size], None, &mut BTreeSet::new())
+        }
+    }
 
-        let mut transaction = client.transaction()?;
+    /// A batch for `table` can be pipelined if it neither depends on rows created in the same transaction nor needs
+    /// rows of other tables to be created with it, and can't violate checks that were not understood.
+    fn pipelined(&self, key_pool: &KeyPool, table: &TableName) -> bool {
+        self.params.pipeline > 1
+            && !self.db.copy
+            && !self.require_afters.contains_key(table)
+            && !self.require_befores.contains_key(table)
+            && !self.db.tables[table].unparsed_checks()
+            && self.db.unresolved_foreign_keys(key_pool, table, None).is_empty()
+    }
 
-        if let Some((rcolumn, atable, acolumn)) = params.require_afters.get(random_table) {
-            let res = db.insert_in_table(&mut transaction, random_table, None, Some(rcolumn));
+    /// Sends all queued insertions at once
+    fn flush(&self, client: &mut impl Connection, key_pool: &mut KeyPool, queue: &mut Vec<Insertion>) -> Result<Vec<(TableName, Row)>, postgres::Error> {
+        let mut insertions = std::mem::take(queue);
+        let statements = insertions.iter_mut()
+            .flat_map(|insertion| std::mem::take(&mut insertion.statements))
+            .collect::<Vec<Statement>>();
+        let mut results = client.pipeline(&statements)?.into_iter();
+
+        let mut new_rows = Vec::<(TableName, Row)>::new();
+        for insertion in insertions {
+            for row in insertion.rows(results.by_ref().take(insertion.lengths.len()).collect()) {
+                key_pool.add_pending(&insertion.table, &row);
+                new_rows.push((insertion.table.clone(), row));
+            }
+        }
 
-            match res {
-                Ok(Some(value)) => {
-                    insertions += 1;
+        Ok(new_rows)
+    }
 
-                    let res = db.insert_in_table(&mut transaction, atable, Some((acolumn, value)), None);
+    fn run(&self, client: &mut impl Connection, key_pool: &mut KeyPool, rng: &mut impl Rng) -> Result<(), postgres::Error> {
+        let (db, params, progress) = (self.db, self.params, self.progress);
 
-                    match res {
-                        Ok(_) => (),
-                        Err(e) => println!("{}", e),
-                    }
-                },
-                Ok(None) => panic!("Got no result!"),
-                Err(e) => println!("{}", e),
+        loop {
+            {
+                let mut progress = progress.lock().unwrap();
+                if self.stop.load(Ordering::SeqCst) || !progress.running(params, self.started, self.table_rows) {
+                    break;
+                }
+                progress.claimed_transactions += 1;
             }
-        } else if let Some((rcolumn, btable, bcolumn)) = params.require_befores.get(random_table) {
-            let res = db.insert_in_table(&mut transaction, btable, None, Some(bcolumn));
 
-            match res {
-                Ok(Some(value)) => {
-                    insertions += 1;
+            client.batch_execute("BEGIN")?;
+            key_pool.clear_pending();
+
+            let mut new_rows = Vec::<(TableName, Row)>::new();
+            let mut queue = Vec::<Insertion>::new();
+            let mut claimed = BTreeMap::<TableName, u64>::new();
+            let mut result = Ok(());
+            let mut finished = false;
+
+            while new_rows.len() + queue.iter().map(Insertion::len).sum::<usize>() < self.rows_per_transaction {
+                let mut progress = progress.lock().unwrap();
+                if params.max_rows.is_some_and(|max_rows| progress.rows + progress.claimed_rows >= max_rows) {
+                    break;
+                }
 
-                    let res = db.insert_in_table(&mut transaction, random_table, Some((rcolumn, value)), None);
+                let candidates = self.tables.iter()
+                    .filter(|table| self.table_rows.get(table).is_none_or(|rows| progress.count(table) < *rows))
+                    .collect::<Vec<&TableName>>();
 
-                    match res {
-                        Ok(_) => (),
-                        Err(e) => println!("{}", e),
+                let mut missing = BTreeMap::<&TableName, Vec<TableName>>::new();
+                for table in &candidates {
+                    let mut missing_keys = db.missing_keys(key_pool, table, None);
+                    if let Some((_, atable, acolumn)) = self.require_afters.get(*table) {
+                        missing_keys.extend(db.missing_keys(key_pool, atable, Some(acolumn)));
+                    }
+                    if let Some((rcolumn, btable, _)) = self.require_befores.get(*table) {
+                        missing_keys = db.missing_keys(key_pool, table, Some(rcolumn));
+                        missing_keys.extend(db.missing_keys(key_pool, btable, None));
                     }
+                    if !missing_keys.is_empty() {
+                        missing.insert(table, missing_keys);
+                    }
+                }
+
+                let ready = candidates.iter()
+                    .filter(|table| !missing.contains_key(**table))
+                    .collect::<Vec<&&TableName>>();
+
+                let random_table: &TableName = match ready.choose_weighted(rng, |table| self.weights.get(**table).copied().unwrap_or(1)) {
+                    Ok(table) => table,
+                    Err(_) => {
+                        for (table, missing_keys) in missing {
+                            println!("Can not create rows for table {} as there are no rows in {}", table, missing_keys.iter().map(TableName::to_string).collect::<Vec<String>>().join(", "));
+                        }
+                        println!("No table left to create rows for");
+                        finished = true;
+                        break;
+                    },
+                };
+
+                let batch_size = params.batch_size
+                    .min(self.table_rows.get(random_table).map_or(usize::MAX, |rows| (rows - progress.count(random_table)) as usize))
+                    .min(params.max_rows.map_or(usize::MAX, |max_rows| (max_rows - progress.rows - progress.claimed_rows) as usize));
+
+                *progress.claimed.entry(random_table.clone()).or_insert(0) += batch_size as u64;
+                progress.claimed_rows += batch_size as u64;
+                *claimed.entry(random_table.clone()).or_insert(0) += batch_size as u64;
+                drop(progress);
+
+                println!("Creating {} new rows for table: {}", batch_size, db.tables[random_table].name);
+
+                // Independent batches are queued and sent together, everything else waits for the queue first
+                let batch = if self.pipelined(key_pool, random_table) {
+                    db.insert_statements(rng, client, key_pool, random_table, &vec![None; batch_size], None)
+                        .and_then(|insertion| {
+                            queue.push(insertion);
+                            if queue.len() >= params.pipeline {
+                                self.flush(client, key_pool, &mut queue)
+                            } else {
+                                Ok(Vec::new())
+                            }
+                        })
+                } else {
+                    self.flush(client, key_pool, &mut queue)
+                        .and_then(|mut rows| {
+                            rows.extend(self.insert_batch(rng, client, key_pool, random_table, batch_size)?);
+                            Ok(rows)
+                        })
+                };
+
+                match batch {
+                    Ok(batch_rows) => new_rows.extend(batch_rows),
+                    Err(e) => {
+                        result = Err(e);
+                        break;
+                    },
+                }
+            }
+
+            if result.is_ok() {
+                match self.flush(client, key_pool, &mut queue) {
+                    Ok(batch_rows) => new_rows.extend(batch_rows),
+                    Err(e) => result = Err(e),
+                }
+            }
+
+            let committed = match result {
+                Ok(_) if new_rows.is_empty() => {
+                    client.batch_execute("ROLLBACK")?;
+                    false
+                },
+                Ok(_) => match client.batch_execute("COMMIT") {
+                    Ok(_) => true,
+                    Err(e) => {
+                        println!("{}", e);
+                        false
+                    },
                 },
-                Ok(None) => panic!("Got no result!"),
-                Err(e) => println!("{}", e),
+                Err(e) => {
+                    println!("{}", e);
+                    client.batch_execute("ROLLBACK")?;
+                    false
+                },
+            };
+
+            let (rows, refresh) = {
+                let mut progress = progress.lock().unwrap();
+                progress.release(&claimed);
+                if committed {
+                    progress.commit(&new_rows);
+                }
+                (progress.rows, committed && progress.transactions % params.key_refresh == 0)
+            };
+
+            if committed {
+                for (table, row) in &new_rows {
+                    key_pool.add(rng, table, row);
+                }
+
+                if refresh {
+                    key_pool.refresh(rng, client)?;
+                }
             }
-        } else {
-            let res = db.insert_in_table(&mut transaction, random_table, None, None);
 
-            match res {
-                Ok(_) => (),
-                Err(e) => println!("{}", e),
+            println!("{}", rows);
+
+            if finished {
+                break;
             }
         }
 
-        let res = transaction.commit();
+        Ok(())
+    }
+}
+
+fn run() -> Result<(), postgres::Error> {
+    let mut client = Client::connect(&std::env::args().nth(1).unwrap(), NoTls)?;
+
+    let params = parse_arguments();
+
+    let schemas = collect_schemas(&mut client, &params)?;
+    let mut db = collect_table_information(&mut client, &schemas)?;
+    db.copy = params.copy;
+    db.non_finite = params.non_finite;
+    db.time_range = params.time_range;
+    db.uuid_v7 = params.uuid_v7;
+    db.array_length = params.array_length;
+    db.array_nulls = params.array_nulls;
+    db.json_depth = params.json_depth;
+    db.json_keys = params.json_keys;
+    db.overriding_system_value = params.overriding_system_value;
+
+    for (table, column, schema) in &params.json_schemas {
+        let table = db.resolve_table(table);
+        match db.tables.get_mut(&table).unwrap().columns.get_mut(column) {
+            Some(column_info) if matches!(column_info.value_type, Type::Json | Type::Jsonb) => column_info.json_schema = Some(schema.clone()),
+            Some(_) => panic!("Column {} of table {} is not of type json or jsonb", column, table),
+            None => panic!("Column {} not found in table {}", column, table),
+        }
+    }
+
+    for (table, column, min, max) in &params.string_lengths {
+        let table = db.resolve_table(table);
+        match db.tables.get_mut(&table).unwrap().columns.get_mut(column) {
+            Some(Column {value_type: Type::Text(Some(max_length)), ..}) if *max > *max_length as usize => panic!("Maximum length {} exceeds the length {} of column {} of table {}", max, max_length, column, table),
+            Some(column_info) if matches!(column_info.value_type, Type::Text(_)) => column_info.string_length = Some((*min, *max)),
+            Some(_) => panic!("Column {} of table {} is not of a string type", column, table),
+            None => panic!("Column {} not found in table {}", column, table),
+        }
+    }
+
+    let onlys = params.onlys.iter()
+        .map(|table| db.resolve_table(table))
+        .collect::<Vec<TableName>>();
+    let skips = params.skips.iter()
+        .map(|table| db.resolve_table(table))
+        .collect::<BTreeSet<TableName>>();
+    let require_afters = params.require_afters.iter()
+        .map(|(table, (column, atable, acolumn))| (db.resolve_table(table), (column.clone(), db.resolve_table(atable), acolumn.clone())))
+        .collect::<BTreeMap<TableName, (String, TableName, String)>>();
+    let require_befores = params.require_befores.iter()
+        .map(|(table, (column, btable, bcolumn))| (db.resolve_table(table), (column.clone(), db.resolve_table(btable), bcolumn.clone())))
+        .collect::<BTreeMap<TableName, (String, TableName, String)>>();
+    let table_rows = params.table_rows.iter()
+        .map(|(table, rows)| (db.resolve_table(table), *rows))
+        .collect::<BTreeMap<TableName, u64>>();
+    let weights = params.weights.iter()
+        .map(|(table, weight)| (db.resolve_table(table), *weight))
+        .collect::<BTreeMap<TableName, u32>>();
+
+    let tables = if !onlys.is_empty() {
+        onlys
+    } else {
+        db.table_names.iter()
+            .filter(|table| !skips.contains(table))
+            .cloned()
+            .collect::<Vec<TableName>>()
+    };
+    let tables = tables.into_iter()
+        .filter(|table| match db.tables[table].foreign_keys.iter().find(|foreign_key| !foreign_key.has_key_type(&db.tables[table])) {
+            Some(foreign_key) => {
+                println!("Skipping table {} as foreign key {} is not of type int2, int4, int8, text or uuid", table, foreign_key.name);
+                false
+            },
+            None => true,
+        })
+        .collect::<Vec<TableName>>();
+
+    for table in table_rows.keys() {
+        if !tables.contains(table) {
+            panic!("Table {} has a row target but is excluded by '--only=' or '--skip='", table);
+        }
+    }
 
-        match res {
-            Ok(_) => insertions += 1,
-            Err(e) => println!("{}", e),
+    let stop = Arc::new(AtomicBool::new(false));
+    let stop_handler = stop.clone();
+    ctrlc::set_handler(move || {
+        if stop_handler.swap(true, Ordering::SeqCst) {
+            std::process::exit(130);
         }
+        println!("Stopping after the current transaction, press CTRL-C again to abort.");
+    }).expect("Could not set CTRL-C handler");
+
+    let seed = params.seed.unwrap_or_else(|| rand::thread_rng().gen());
+    println!("Using seed {}", seed);
+    let mut rng = StdRng::seed_from_u64(seed);
+
+    let key_pool = KeyPool::new(&db, params.key_pool_size, params.max_depth);
+    key_pool.refresh(&mut rng, &mut client)?;
+
+    let rows_per_transaction = params.rows_per_transaction.unwrap_or(params.batch_size * params.pipeline);
+    let worker_seeds = (0..params.jobs).map(|_| rng.gen()).collect::<Vec<u64>>();
+
+    let started = Instant::now();
+    let progress = Mutex::new(Progress::default());
+
+    let worker = Worker {
+        db: &db,
+        params: &params,
+        tables: &tables,
+        require_afters: &require_afters,
+        require_befores: &require_befores,
+        table_rows: &table_rows,
+        weights: &weights,
+        stop: &stop,
+        progress: &progress,
+        started,
+        rows_per_transaction,
+    };
+
+    std::thread::scope(|scope| {
+        let workers = worker_seeds.into_iter()
+            .map(|worker_seed| {
+                let worker = &worker;
+                let mut key_pool = key_pool.clone();
+
+                scope.spawn(move || -> Result<(), postgres::Error> {
+                    let mut rng = StdRng::seed_from_u64(worker_seed);
+                    let connection = std::env::args().nth(1).unwrap();
 
-        println!("{}", insertions);
+                    if worker.params.pipeline > 1 {
+                        worker.run(&mut AsyncClient::connect(&connection)?, &mut key_pool, &mut rng)
+                    } else {
+                        worker.run(&mut Client::connect(&connection, NoTls)?, &mut key_pool, &mut rng)
+                    }
+                })
+            })
+            .collect::<Vec<_>>();
+
+        workers.into_iter()
+            .try_for_each(|worker| worker.join().unwrap())
+    })?;
+
+    let progress = progress.into_inner().unwrap();
+    println!("Inserted rows:");
+    for (table, count) in &progress.inserted {
+        println!("  {}: {}", table, count);
     }
+    println!("Total: {} rows in {} transactions in {:.1}s", progress.rows, progress.transactions, started.elapsed().as_secs_f64());
+
+    Ok(())
 }
 
 fn main() {
     run().unwrap();
 }
 
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn checks(definition: &str, subjects: &[&str]) -> Vec<(String, Check)> {
+        parse_checks(definition, &subjects.iter().map(|subject| subject.to_string()).collect::<Vec<String>>())
+    }
+
+    fn check(subject: &str, check: Check) -> (String, Check) {
+        (subject.to_string(), check)
+    }
+
+    #[test]
+    fn split_top_level_ignores_nested_and_quoted_separators() {
+        assert_eq!(split_top_level("a, 'b, c', f(d, e), ARRAY[f, g]", ", "), vec!["a", "'b, c'", "f(d, e)", "ARRAY[f, g]"]);
+        assert_eq!(split_top_level("(a AND b) AND c", " AND "), vec!["(a AND b)", "c"]);
+    }
+
+    #[test]
+    fn split_top_level_handles_non_ascii_and_quoted_identifiers() {
+        assert_eq!(split_top_level("(\"größe\" > 0) AND (ä < 'ö, ü')", " AND "), vec!["(\"größe\" > 0)", "(ä < 'ö, ü')"]);
+        assert_eq!(split_top_level("\"a, b\", \"c)\", d", ", "), vec!["\"a, b\"", "\"c)\"", "d"]);
+        assert_eq!(checks("CHECK ((\"größe\" > 0))", &["größe"]), vec![check("größe", Check::Minimum(0.0, false))]);
+        assert_eq!(strip_expression("(\"a(\")"), "\"a(\"");
+    }
+
+    #[test]
+    fn strip_expression_removes_parentheses_and_casts() {
+        assert_eq!(strip_expression("((VALUE)::text)"), "VALUE");
+        assert_eq!(strip_expression("(0)::numeric"), "0");
+        assert_eq!(strip_expression("'a b'::character varying"), "'a b'");
+        assert_eq!(strip_expression("(ARRAY['a'::text])::text[]"), "ARRAY['a'::text]");
+        assert_eq!(strip_expression("(a > 0) AND (b > 0)"), "(a > 0) AND (b > 0)");
+        assert_eq!(strip_expression("(VALUE)::text ~ 'x'::text"), "(VALUE)::text ~ 'x'::text");
+    }
+
+    #[test]
+    fn parse_checks_in_lists() {
+        assert_eq!(checks("CHECK ((status = ANY (ARRAY['a'::text, 'b''c'::text])))", &["status"]),
+                   vec![check("status", Check::OneOf(vec!["a".to_string(), "b'c".to_string()]))]);
+        assert_eq!(checks("CHECK (((VALUE)::text = ANY ((ARRAY['YES'::character varying, 'NO'::character varying])::text[])))", &["VALUE"]),
+                   vec![check("VALUE", Check::OneOf(vec!["YES".to_string(), "NO".to_string()]))]);
+    }
+
+    #[test]
+    fn parse_checks_lengths() {
+        assert_eq!(checks("CHECK ((char_length((code)::text) = 8))", &["code"]),
+                   vec![check("code", Check::MinLength(8)), check("code", Check::MaxLength(8))]);
+        assert_eq!(checks("CHECK ((length((l)::text) >= 3))", &["l"]), vec![check("l", Check::MinLength(3))]);
+        assert_eq!(checks("CHECK ((5 > length(name)))", &["name"]), vec![check("name", Check::MaxLength(4))]);
+    }
+
+    #[test]
+    fn parse_checks_comparisons() {
+        assert_eq!(checks("CHECK ((price >= (0)::numeric))", &["price"]), vec![check("price", Check::Minimum(0.0, true))]);
+        assert_eq!(checks("CHECK ((0 < price))", &["price"]), vec![check("price", Check::Minimum(0.0, false))]);
+        assert_eq!(checks("CHECK (((VALUE >= (0)::numeric) AND (VALUE <= (5)::numeric)))", &["VALUE"]),
+                   vec![check("VALUE", Check::Minimum(0.0, true)), check("VALUE", Check::Maximum(5.0, true))]);
+        assert_eq!(checks("CHECK ((VALUE > '-5'::integer))", &["VALUE"]), vec![check("VALUE", Check::Minimum(-5.0, false))]);
+        assert_eq!(checks("CHECK (((qty >= 1) AND (qty <= 10) AND (\"Weird\" <> 3)))", &["qty", "Weird"]),
+                   vec![check("qty", Check::Minimum(1.0, true)), check("qty", Check::Maximum(10.0, true)), check("Weird", Check::NotEqual("3".to_string()))]);
+    }
+
+    #[test]
+    fn parse_checks_columns_regexes_and_nulls() {
+        assert_eq!(checks("CHECK ((start_at < end_at))", &["start_at", "end_at"]), vec![check("start_at", Check::Before("end_at".to_string()))]);
+        assert_eq!(checks("CHECK ((hi >= lo))", &["hi", "lo"]), vec![check("lo", Check::Before("hi".to_string()))]);
+        assert_eq!(checks("CHECK (((VALUE)::text ~ '^[A-Z]{3}$'::text))", &["VALUE"]), vec![check("VALUE", Check::Matches("^[A-Z]{3}$".to_string()))]);
+        assert_eq!(checks("CHECK ((x IS NOT NULL)) NOT VALID", &["x"]), vec![check("x", Check::NotNull)]);
+    }
+
+    #[test]
+    fn parse_checks_marks_unknown_conditions() {
+        assert_eq!(checks("CHECK (((a + b) > 0))", &["a", "b"]), vec![check("a", Check::Unparsed), check("b", Check::Unparsed)]);
+        assert_eq!(checks("CHECK (((s IS NULL) OR (length(s) > 2)))", &["s"]), vec![check("s", Check::Unparsed)]);
+        assert_eq!(checks("CHECK (((d > '2020-01-01'::date) AND (n > 1)))", &["d", "n"]),
+                   vec![check("d", Check::Unparsed), check("n", Check::Unparsed), check("n", Check::Minimum(1.0, false))]);
+    }
+
+    #[test]
+    fn rand_matching_classes_and_quantifiers() {
+        let mut rng = StdRng::seed_from_u64(1);
+        for _ in 0..100 {
+            let text = rand_matching(&mut rng, "^[A-Z]{3}$", 4).unwrap();
+            assert!(text.len() == 3 && text.chars().all(|c| c.is_ascii_uppercase()), "{}", text);
+
+            assert_eq!(rand_matching(&mut rng, "a{1}b{1,1}c", 4).unwrap(), "abc");
+
+            let text = rand_matching(&mut rng, "^(ab|cd)+\\d{2}[^a-z]?$", 4).unwrap();
+            let mut rest = text.as_str();
+            let mut groups = 0;
+            while let Some(stripped) = rest.strip_prefix("ab").or_else(|| rest.strip_prefix("cd")) {
+                rest = stripped;
+                groups += 1;
+            }
+            assert!((1..=4).contains(&groups) && (2..=3).contains(&rest.len()), "{}", text);
+            assert!(rest[..2].chars().all(|c| c.is_ascii_digit()) && !rest.ends_with(|c: char| c.is_ascii_lowercase()), "{}", text);
+
+            let text = rand_matching(&mut rng, "\\d{2,4}-?x*", 4).unwrap();
+            let number = text.trim_end_matches('x').trim_end_matches('-');
+            assert!((2..=4).contains(&number.len()) && number.chars().all(|c| c.is_ascii_digit()) && text.len() - number.len() <= 5, "{}", text);
+
+            let text = rand_matching(&mut rng, "\\w\\s\\W", 4).unwrap();
+            let chars = text.chars().collect::<Vec<char>>();
+            assert!(chars[0].is_ascii_alphanumeric() || chars[0] == '_', "{}", text);
+            assert!(chars[1] == ' ' && !chars[2].is_ascii_alphanumeric() && chars[2] != '_', "{}", text);
+
+            let text = rand_matching(&mut rng, "[0-9A-F]+", 8).unwrap();
+            assert!((1..=8).contains(&text.len()) && text.chars().all(|c| c.is_ascii_hexdigit() && !c.is_ascii_lowercase()), "{}", text);
+        }
+    }
+
+    #[test]
+    fn rand_matching_rejects_unsupported_patterns() {
+        let mut rng = StdRng::seed_from_u64(1);
+        assert_eq!(rand_matching(&mut rng, "(?=a)b", 4), None);
+        assert_eq!(rand_matching(&mut rng, "(a)\\1", 4), None);
+        assert_eq!(rand_matching(&mut rng, "a{3,1}", 4), None);
+        assert_eq!(rand_matching(&mut rng, "(a", 4), None);
+    }
+
+    #[test]
+    fn checked_value_converts_to_column_type() {
+        let (value, key) = checked_value(&Type::Numeric {precision: Some(4), scale: Some(2)}, "-12.50");
+        assert_eq!(format!("{:?}", value), "Finite { negative: true, digits: \"1250\", scale: 2 }");
+        assert_eq!(key, None);
+
+        let (value, key) = checked_value(&Type::Int4, "42");
+        assert_eq!(format!("{:?}", value), "Int4(42)");
+        assert_eq!(key, Some(Value::Int4(42)));
+
+        let (_, key) = checked_value(&Type::Text(Some(3)), "abc");
+        assert_eq!(key, Some(Value::Text("abc".to_string())));
+    }
+
+    #[test]
+    fn rand_checked_satisfies_checks() {
+        let db = Database::default();
+        let mut rng = StdRng::seed_from_u64(1);
+        for _ in 0..100 {
+            let value = db.rand_checked(&mut rng, &Type::Int4, &[Check::Minimum(0.0, false), Check::Maximum(10.0, true), Check::NotEqual("5".to_string())]).unwrap();
+            assert!((1..=10).contains(&value.parse::<i32>().unwrap()) && value != "5", "{}", value);
+
+            let value = db.rand_checked(&mut rng, &Type::Numeric {precision: Some(3), scale: Some(1)}, &[Check::Minimum(0.0, true)]).unwrap();
+            let number = value.parse::<f64>().unwrap();
+            assert!((0.0..=99.9).contains(&number) && value.split_once('.').unwrap().1.len() == 1, "{}", value);
+
+            let value = db.rand_checked(&mut rng, &Type::Text(Some(10)), &[Check::MinLength(8), Check::MaxLength(8), Check::Matches("^[0-9A-F]+$".to_string())]).unwrap();
+            assert!(value.len() == 8 && value.chars().all(|c| c.is_ascii_hexdigit()), "{}", value);
+        }
+        assert_eq!(db.rand_checked(&mut rng, &Type::Int4, &[Check::NotNull]), None);
+
+        // Constants of other types are cast by the database, not sent as the text of binary values
+        let dates = [Check::OneOf(vec!["2020-01-01".to_string()])];
+        assert_eq!(db.rand_checked(&mut rng, &Type::Date, &dates), None);
+        assert!(!satisfiable(&Type::Array(Box::new(Type::Date), 1), &dates[0]));
+
+        let statuses = Type::Enum(vec!["active".to_string(), "deleted".to_string()]);
+        for _ in 0..10 {
+            assert_eq!(db.rand_checked(&mut rng, &statuses, &[Check::NotEqual("deleted".to_string())]).unwrap(), "active");
+        }
+    }
+
+    #[test]
+    fn unsatisfiable_checks() {
+        assert!(satisfiable(&Type::Int2, &Check::Minimum(0.0, true)));
+        assert!(satisfiable(&Type::Array(Box::new(Type::Text(None)), 1), &Check::Matches("^a$".to_string())));
+        assert!(satisfiable(&Type::Enum(vec!["a".to_string()]), &Check::NotEqual("a".to_string())));
+        assert!(!satisfiable(&Type::Date, &Check::Minimum(0.0, true)));
+        assert!(!satisfiable(&Type::Uuid, &Check::NotEqual("x".to_string())));
+
+        assert!(orderable(&Type::Int2, &Type::Int2));
+        assert!(orderable(&Type::Numeric {precision: Some(10), scale: Some(2)}, &Type::Numeric {precision: Some(10), scale: Some(2)}));
+        assert!(!orderable(&Type::Numeric {precision: Some(10), scale: Some(2)}, &Type::Numeric {precision: Some(5), scale: Some(2)}));
+        assert!(!orderable(&Type::Int4, &Type::Int8));
+        assert!(!orderable(&Type::Text(None), &Type::Text(None)));
+    }
+
+    #[test]
+    fn unique_values_forget_the_oldest() {
+        let mut unique = UniqueValues::default();
+        for i in 0..4 {
+            unique.insert(vec![Value::Int4(i)], 3);
+        }
+        unique.insert(vec![Value::Int4(3)], 3);
+        assert_eq!(unique.values, BTreeSet::from([vec![Value::Int4(1)], vec![Value::Int4(2)], vec![Value::Int4(3)]]));
+        assert_eq!(unique.order.len(), 3);
+    }
+
+    fn encode(value: &dyn ToSql, ty: &postgres::types::Type) -> Vec<u8> {
+        let mut out = postgres::types::private::BytesMut::new();
+        assert!(matches!(value.to_sql_checked(ty, &mut out).unwrap(), postgres::types::IsNull::No));
+        out.to_vec()
+    }
+
+    fn numeric(negative: bool, digits: &str, scale: i32) -> Vec<u8> {
+        encode(&Numeric::Finite {negative, digits: digits.to_string(), scale}, &postgres::types::Type::NUMERIC)
+    }
+
+    #[test]
+    fn numeric_encodes_groups_of_four_digits() {
+        // Number of groups, weight of the first group, sign, display scale and the groups
+        assert_eq!(numeric(false, "1250", 2), [0, 2, 0, 0, 0, 0, 0, 2, 0, 12, 0x13, 0x88]);
+        assert_eq!(numeric(false, "50", 4), [0, 1, 0xff, 0xff, 0, 0, 0, 4, 0, 50]);
+        assert_eq!(numeric(true, "12", -3), [0, 2, 0, 1, 0x40, 0, 0, 0, 0, 1, 0x07, 0xd0]);
+        assert_eq!(numeric(true, "000", 2), [0, 0, 0, 0, 0, 0, 0, 2]);
+        assert_eq!(encode(&Numeric::NaN, &postgres::types::Type::NUMERIC), [0, 0, 0, 0, 0xc0, 0, 0, 0]);
+        assert_eq!(encode(&Numeric::NegativeInfinity, &postgres::types::Type::NUMERIC), [0, 0, 0, 0, 0xf0, 0, 0, 0]);
+    }
+
+    #[test]
+    fn time_values_encode_microseconds() {
+        let interval = Interval {months: 14, days: -3, microseconds: 1_500_000};
+        assert_eq!(encode(&interval, &postgres::types::Type::INTERVAL), [0, 0, 0, 0, 0, 0x16, 0xe3, 0x60, 0xff, 0xff, 0xff, 0xfd, 0, 0, 0, 14]);
+
+        // 01:02:03.000004 at UTC+02:00, which is -7200 seconds west of UTC
+        let time = TimeTz {time: Time::from_hms_micro(1, 2, 3, 4).unwrap(), offset: UtcOffset::from_hms(2, 0, 0).unwrap()};
+        assert_eq!(encode(&time, &postgres::types::Type::TIMETZ), [0, 0, 0, 0, 0xdd, 0xe8, 0x78, 0xc4, 0xff, 0xff, 0xe3, 0xe0]);
+    }
+
+    #[test]
+    fn inet_encodes_family_netmask_and_cidr_flag() {
+        let cidr = Inet {address: std::net::IpAddr::from([192, 168, 16, 0]), netmask: 20};
+        assert_eq!(encode(&cidr, &postgres::types::Type::CIDR), [2, 20, 1, 4, 192, 168, 16, 0]);
+        let inet = Inet {address: std::net::IpAddr::from(std::net::Ipv6Addr::LOCALHOST), netmask: 128};
+        assert_eq!(encode(&inet, &postgres::types::Type::INET), [[3, 128, 0, 16].as_slice(), &[0; 15], &[1]].concat());
+
+        let mut rng = StdRng::seed_from_u64(1);
+        for _ in 0..100 {
+            let cidr = rand_inet(&mut rng, true);
+            let (address, bits) = match cidr.address {
+                std::net::IpAddr::V4(address) => (u32::from(address) as u128, 32),
+                std::net::IpAddr::V6(address) => (u128::from(address), 128),
+            };
+            let host = u128::MAX.checked_shr(128 - (bits - cidr.netmask as u32)).unwrap_or(0);
+            assert_eq!(address & host, 0, "{:?}", cidr);
+        }
+    }
+
+    #[test]
+    fn containers_encode_their_elements() {
+        let range = RangeValue::Bounds {lower: Some((Box::new(1i32), true)), upper: None};
+        assert_eq!(encode(&range, &postgres::types::Type::INT4_RANGE), [0x12, 0, 0, 0, 4, 0, 0, 0, 1]);
+        let range = RangeValue::Bounds {lower: Some((Box::new(1i32), false)), upper: Some((Box::new(2i32), true))};
+        assert_eq!(encode(&range, &postgres::types::Type::INT4_RANGE), [0x04, 0, 0, 0, 4, 0, 0, 0, 1, 0, 0, 0, 4, 0, 0, 0, 2]);
+        assert_eq!(encode(&RangeValue::Empty, &postgres::types::Type::INT4_RANGE), [0x01]);
+
+        // Dimensions, NULL flag, element type, length and lower bound of each dimension and the elements
+        let array = ArrayValue {dimensions: vec![2], elements: vec![Some(Box::new(7i32)), None]};
+        assert_eq!(encode(&array, &postgres::types::Type::INT4_ARRAY),
+                   [0, 0, 0, 1, 0, 0, 0, 1, 0, 0, 0, 23, 0, 0, 0, 2, 0, 0, 0, 1, 0, 0, 0, 4, 0, 0, 0, 7, 0xff, 0xff, 0xff, 0xff]);
+
+        let pair = postgres::types::Type::new("pair".to_string(), 0, postgres::types::Kind::Composite(vec![
+            postgres::types::Field::new("a".to_string(), postgres::types::Type::INT4),
+            postgres::types::Field::new("b".to_string(), postgres::types::Type::TEXT),
+        ]), "public".to_string());
+        let composite = CompositeValue {fields: vec![Some(Box::new(7i32)), None]};
+        assert_eq!(encode(&composite, &pair), [0, 0, 0, 2, 0, 0, 0, 23, 0, 0, 0, 4, 0, 0, 0, 7, 0, 0, 0, 25, 0xff, 0xff, 0xff, 0xff]);
+        let composite = CompositeValue {fields: vec![None]};
+        assert!(composite.to_sql_checked(&pair, &mut postgres::types::private::BytesMut::new()).is_err());
+    }
+}
+
 
 
 /* This file is part of pgGenerate.